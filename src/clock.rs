@@ -0,0 +1,804 @@
+//! The clocks feeding the audio ports, and the sample rates they produce.
+//!
+//! A master port divides its clock source by [`PortMasterClockDivider`] to get LRCK and drives
+//! BCK at 64 times that. A [`ClockTree`] holds the frequencies on the clock pins, so the rates a
+//! [`PortConfig`] generates can be worked out before the port is configured.
+//!
+//! [`Rate`] holds the rates of IEC 60958, with their channel status codes and families. A
+//! measured [`SampleRate`] snaps to one with [`Rate::nearest`]. Everything is integer or fixed
+//! point arithmetic, so nothing pulls in soft float.
+//!
+//! ```
+//! use src4392::clock::{ClockTree, SampleRate};
+//! use src4392::config::PortConfig;
+//! use src4392::PortMasterClockDivider;
+//!
+//! let clocks = ClockTree::new(24_576_000);
+//! let divider = PortMasterClockDivider::for_rate(24_576_000, SampleRate::from_hz(96_000)).unwrap();
+//! let port = PortConfig {
+//!     clock_divider: divider,
+//!     is_master: true,
+//!     ..PortConfig::RESET
+//! };
+//! assert_eq!(clocks.port_clocks(&port).unwrap().lrck, SampleRate::from_hz(96_000));
+//! ```
+
+use core::fmt;
+
+use crate::config::PortConfig;
+use crate::port_control::{AudioFormat, PortClockSource, PortMasterClockDivider};
+use crate::receiver::RecoveredClockDivider;
+use crate::sample_rate_converter::{FixedU16, U11};
+use crate::transmitter::{TransmitterClockDivider, TransmitterClockSource};
+use crate::Port;
+
+/// A sample rate, in Hz.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SampleRate(u32);
+
+impl SampleRate {
+    pub const fn from_hz(hz: u32) -> Self {
+        SampleRate(hz)
+    }
+
+    pub const fn hz(&self) -> u32 {
+        self.0
+    }
+
+    /// The ratio of this rate to `output` in the format of [`SrcRatio`], rounded to the
+    /// nearest step. This is what the SRC reports when converting from this rate to `output`.
+    /// `None` when `output` is zero or the ratio doesn't fit.
+    ///
+    /// [`SrcRatio`]: crate::SrcRatio
+    pub const fn src_ratio(&self, output: SampleRate) -> Option<FixedU16<U11>> {
+        if output.0 == 0 {
+            return None;
+        }
+        let output = output.0 as u64;
+        let bits = ((self.0 as u64) * (1 << 11) + output / 2) / output;
+        if bits > u16::MAX as u64 {
+            return None;
+        }
+        Some(FixedU16::<U11>::from_bits(bits as u16))
+    }
+}
+
+impl fmt::Display for SampleRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Hz", self.0)
+    }
+}
+
+/// The sample rates of IEC 60958, from the 32, 44.1 and 48 kHz families.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rate {
+    Hz22050,
+    Hz24000,
+    Hz32000,
+    Hz44100,
+    Hz48000,
+    Hz88200,
+    Hz96000,
+    Hz176400,
+    Hz192000,
+    Hz768000,
+}
+
+/// The base rate a [`Rate`] is a power of two multiple of.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RateFamily {
+    Hz32000,
+    Hz44100,
+    Hz48000,
+}
+
+impl RateFamily {
+    pub const fn base(&self) -> Rate {
+        match self {
+            RateFamily::Hz32000 => Rate::Hz32000,
+            RateFamily::Hz44100 => Rate::Hz44100,
+            RateFamily::Hz48000 => Rate::Hz48000,
+        }
+    }
+}
+
+impl Rate {
+    pub const ALL: [Rate; 10] = [
+        Rate::Hz22050,
+        Rate::Hz24000,
+        Rate::Hz32000,
+        Rate::Hz44100,
+        Rate::Hz48000,
+        Rate::Hz88200,
+        Rate::Hz96000,
+        Rate::Hz176400,
+        Rate::Hz192000,
+        Rate::Hz768000,
+    ];
+
+    pub const fn hz(&self) -> u32 {
+        match self {
+            Rate::Hz22050 => 22_050,
+            Rate::Hz24000 => 24_000,
+            Rate::Hz32000 => 32_000,
+            Rate::Hz44100 => 44_100,
+            Rate::Hz48000 => 48_000,
+            Rate::Hz88200 => 88_200,
+            Rate::Hz96000 => 96_000,
+            Rate::Hz176400 => 176_400,
+            Rate::Hz192000 => 192_000,
+            Rate::Hz768000 => 768_000,
+        }
+    }
+
+    pub const fn sample_rate(&self) -> SampleRate {
+        SampleRate(self.hz())
+    }
+
+    pub const fn family(&self) -> RateFamily {
+        match self {
+            Rate::Hz32000 => RateFamily::Hz32000,
+            Rate::Hz22050 | Rate::Hz44100 | Rate::Hz88200 | Rate::Hz176400 => RateFamily::Hz44100,
+            Rate::Hz24000 | Rate::Hz48000 | Rate::Hz96000 | Rate::Hz192000 | Rate::Hz768000 => {
+                RateFamily::Hz48000
+            }
+        }
+    }
+
+    /// The standard rate closest to `rate`.
+    pub fn nearest(rate: SampleRate) -> Rate {
+        Rate::ALL
+            .into_iter()
+            .min_by_key(|r| r.hz().abs_diff(rate.hz()))
+            .unwrap()
+    }
+
+    /// The sampling frequency code of IEC 60958-3 consumer channel status, bits 24 to 27 with
+    /// bit 24 as the least significant bit.
+    pub const fn channel_status_code(&self) -> u8 {
+        match self {
+            Rate::Hz22050 => 0x4,
+            Rate::Hz24000 => 0x6,
+            Rate::Hz32000 => 0x3,
+            Rate::Hz44100 => 0x0,
+            Rate::Hz48000 => 0x2,
+            Rate::Hz88200 => 0x8,
+            Rate::Hz96000 => 0xA,
+            Rate::Hz176400 => 0xC,
+            Rate::Hz192000 => 0xE,
+            Rate::Hz768000 => 0x9,
+        }
+    }
+
+    /// The rate of a consumer channel status sampling frequency code. `None` for "not
+    /// indicated", 0x1, and the codes IEC 60958-3 doesn't assign.
+    pub const fn from_channel_status_code(code: u8) -> Option<Rate> {
+        match code {
+            0x4 => Some(Rate::Hz22050),
+            0x6 => Some(Rate::Hz24000),
+            0x3 => Some(Rate::Hz32000),
+            0x0 => Some(Rate::Hz44100),
+            0x2 => Some(Rate::Hz48000),
+            0x8 => Some(Rate::Hz88200),
+            0xA => Some(Rate::Hz96000),
+            0xC => Some(Rate::Hz176400),
+            0xE => Some(Rate::Hz192000),
+            0x9 => Some(Rate::Hz768000),
+            _ => None,
+        }
+    }
+
+    /// The standard rate of exactly `rate`, if there is one.
+    pub fn from_sample_rate(rate: SampleRate) -> Option<Rate> {
+        Rate::ALL.into_iter().find(|r| r.hz() == rate.hz())
+    }
+}
+
+impl From<Rate> for SampleRate {
+    fn from(rate: Rate) -> Self {
+        rate.sample_rate()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClockError {
+    /// The port is a slave, its clocks come from outside the device.
+    SlavePort,
+    /// Nothing is known to drive the selected clock source.
+    MissingClock(PortClockSource),
+    /// Nothing is known to drive the clock source selected for the DIT.
+    MissingTransmitterClock(TransmitterClockSource),
+    /// The rate is outside what the audio ports support.
+    RateOutOfRange(SampleRate),
+    /// The port is set to a reserved audio format.
+    ReservedFormat(AudioFormat),
+    /// The clock can't be divided down to the rate by any of the port dividers.
+    NoDivider { clock_hz: u32, rate: SampleRate },
+    /// RXCKO is selected as a clock source but the recovered clock output is disabled.
+    RecoveredClockDisabled(RxckoUser),
+    /// RXCKO is selected as a clock source but the DIR isn't locked, so there is no clock to
+    /// recover.
+    ReceiverUnlocked(RxckoUser),
+}
+
+/// A block that can take its clock from RXCKO.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RxckoUser {
+    /// A master audio port.
+    Port(Port),
+    Src,
+}
+
+/// The clocks a master port generates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PortClocks {
+    pub lrck: SampleRate,
+    pub bck_hz: u32,
+}
+
+/// The frequencies on the device's clock pins.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ClockTree {
+    /// The master clock on MCLK.
+    pub mclk_hz: u32,
+    /// The reference clock on RXCKI, if one is fitted.
+    pub rxcki_hz: Option<u32>,
+    /// The clock recovered by the DIR on RXCKO, while it is locked and the output is enabled.
+    pub rxcko_hz: Option<u32>,
+}
+
+impl ClockTree {
+    /// The highest LRCK the audio ports support.
+    pub const MAX_PORT_RATE: SampleRate = SampleRate(216_000);
+    /// The highest frame rate the DIT supports.
+    pub const MAX_TRANSMITTER_RATE: SampleRate = SampleRate(216_000);
+    /// BCK of a master port, in periods per LRCK period.
+    pub const MASTER_BCK_PER_FRAME: u32 = 64;
+    /// The master clock recovered by the DIR, in periods per frame of the received stream,
+    /// before [`RecoveredClockDivider`] is applied.
+    pub const RECOVERED_CLOCK_PER_FRAME: u32 = 512;
+
+    /// A tree with only MCLK running.
+    pub const fn new(mclk_hz: u32) -> Self {
+        ClockTree {
+            mclk_hz,
+            rxcki_hz: None,
+            rxcko_hz: None,
+        }
+    }
+
+    pub const fn with_rxcki(mut self, rxcki_hz: u32) -> Self {
+        self.rxcki_hz = Some(rxcki_hz);
+        self
+    }
+
+    pub const fn with_rxcko(mut self, rxcko_hz: u32) -> Self {
+        self.rxcko_hz = Some(rxcko_hz);
+        self
+    }
+
+    /// The frequency on RXCKO while the DIR is locked to a stream at `input`.
+    pub const fn recovered_clock_hz(input: SampleRate, divider: RecoveredClockDivider) -> u32 {
+        input.0 * Self::RECOVERED_CLOCK_PER_FRAME / divider.ratio()
+    }
+
+    /// The frequency of a port clock source.
+    pub const fn source_hz(&self, source: PortClockSource) -> Option<u32> {
+        match source {
+            PortClockSource::Mclk => Some(self.mclk_hz),
+            PortClockSource::Rxcki => self.rxcki_hz,
+            PortClockSource::Rxcko => self.rxcko_hz,
+            PortClockSource::Reserved => None,
+        }
+    }
+
+    /// The frequency of a DIT clock source.
+    pub const fn transmitter_source_hz(&self, source: TransmitterClockSource) -> Option<u32> {
+        match source {
+            TransmitterClockSource::Mclk => Some(self.mclk_hz),
+            TransmitterClockSource::Rxcki => self.rxcki_hz,
+        }
+    }
+
+    /// The LRCK and BCK a master port generates with `config`, rounded down to whole Hz.
+    ///
+    /// Every audio format fits in the 32 BCK periods per channel a master port gives it, so the
+    /// format only fails the check when it is reserved.
+    pub fn port_clocks(&self, config: &PortConfig) -> Result<PortClocks, ClockError> {
+        if !config.is_master {
+            return Err(ClockError::SlavePort);
+        }
+        if matches!(
+            config.audio_format,
+            AudioFormat::Reserved010 | AudioFormat::Reserved011
+        ) {
+            return Err(ClockError::ReservedFormat(config.audio_format));
+        }
+        let clock_hz = self
+            .source_hz(config.clock_source)
+            .ok_or(ClockError::MissingClock(config.clock_source))?;
+        let lrck = SampleRate(clock_hz / config.clock_divider.ratio());
+        if lrck > Self::MAX_PORT_RATE || lrck.hz() == 0 {
+            return Err(ClockError::RateOutOfRange(lrck));
+        }
+        Ok(PortClocks {
+            lrck,
+            bck_hz: lrck.hz() * Self::MASTER_BCK_PER_FRAME,
+        })
+    }
+}
+
+impl PortMasterClockDivider {
+    /// The number of clock periods per LRCK period.
+    pub const fn ratio(&self) -> u32 {
+        match self {
+            PortMasterClockDivider::_128 => 128,
+            PortMasterClockDivider::_256 => 256,
+            PortMasterClockDivider::_384 => 384,
+            PortMasterClockDivider::_512 => 512,
+        }
+    }
+
+    /// The divider that takes `clock_hz` exactly to `rate`.
+    pub fn for_rate(clock_hz: u32, rate: SampleRate) -> Result<Self, ClockError> {
+        if rate > ClockTree::MAX_PORT_RATE || rate.hz() == 0 {
+            return Err(ClockError::RateOutOfRange(rate));
+        }
+        [
+            PortMasterClockDivider::_128,
+            PortMasterClockDivider::_256,
+            PortMasterClockDivider::_384,
+            PortMasterClockDivider::_512,
+        ]
+        .into_iter()
+        .find(|divider| u64::from(rate.hz()) * u64::from(divider.ratio()) == u64::from(clock_hz))
+        .ok_or(ClockError::NoDivider { clock_hz, rate })
+    }
+}
+
+impl TransmitterClockDivider {
+    /// The number of clock periods per transmitted frame.
+    pub const fn ratio(&self) -> u32 {
+        match self {
+            TransmitterClockDivider::_128 => 128,
+            TransmitterClockDivider::_256 => 256,
+            TransmitterClockDivider::_384 => 384,
+            TransmitterClockDivider::_512 => 512,
+        }
+    }
+
+    /// The divider that takes `clock_hz` exactly to the frame rate `rate`.
+    pub fn for_rate(clock_hz: u32, rate: SampleRate) -> Result<Self, ClockError> {
+        if rate > ClockTree::MAX_TRANSMITTER_RATE || rate.hz() == 0 {
+            return Err(ClockError::RateOutOfRange(rate));
+        }
+        [
+            TransmitterClockDivider::_128,
+            TransmitterClockDivider::_256,
+            TransmitterClockDivider::_384,
+            TransmitterClockDivider::_512,
+        ]
+        .into_iter()
+        .find(|divider| u64::from(rate.hz()) * u64::from(divider.ratio()) == u64::from(clock_hz))
+        .ok_or(ClockError::NoDivider { clock_hz, rate })
+    }
+}
+
+impl RecoveredClockDivider {
+    pub const fn ratio(&self) -> u32 {
+        match self {
+            RecoveredClockDivider::_1 => 1,
+            RecoveredClockDivider::_2 => 2,
+            RecoveredClockDivider::_4 => 4,
+            RecoveredClockDivider::_8 => 8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use super::*;
+    use crate::dump::{BUFFER_PAGE_LEN, FULL_DUMP_LEN, REGISTER_DUMP_LEN};
+    use crate::interrupt::InterruptMode;
+    use crate::port_control::OutputDataSource;
+    use crate::receiver::{ReceiverInterruptMask1, ReceiverInterruptMode2, ReceiverStatus1};
+    use crate::registers::Registers;
+    use crate::sample_rate_converter::{
+        Deemphasis, InterpolationFilterGroupDelay, SrcClockSource, SrcSource,
+    };
+    use crate::sim::fixture::device;
+    use crate::sim::{Event, SimulatedSrc4392};
+    use crate::{Error, ReadModifyWriteSpiRegister};
+
+    fn master(clock_source: PortClockSource, clock_divider: PortMasterClockDivider) -> PortConfig {
+        PortConfig {
+            clock_source,
+            clock_divider,
+            is_master: true,
+            ..PortConfig::RESET
+        }
+    }
+
+    #[test]
+    fn master_port_clocks() {
+        let clocks = ClockTree::new(22_579_200).with_rxcko(12_288_000);
+        let port = master(PortClockSource::Mclk, PortMasterClockDivider::_512);
+        assert_eq!(
+            clocks.port_clocks(&port),
+            Ok(PortClocks {
+                lrck: SampleRate::from_hz(44_100),
+                bck_hz: 2_822_400,
+            })
+        );
+
+        let port = master(PortClockSource::Rxcko, PortMasterClockDivider::_128);
+        assert_eq!(
+            clocks.port_clocks(&port).unwrap().lrck,
+            SampleRate::from_hz(96_000)
+        );
+    }
+
+    #[test]
+    fn port_clock_errors() {
+        let clocks = ClockTree::new(24_576_000);
+        assert_eq!(
+            clocks.port_clocks(&PortConfig::RESET),
+            Err(ClockError::SlavePort)
+        );
+        assert_eq!(
+            clocks.port_clocks(&master(
+                PortClockSource::Rxcki,
+                PortMasterClockDivider::_128
+            )),
+            Err(ClockError::MissingClock(PortClockSource::Rxcki))
+        );
+        assert_eq!(
+            ClockTree::new(49_152_000)
+                .port_clocks(&master(PortClockSource::Mclk, PortMasterClockDivider::_128)),
+            Err(ClockError::RateOutOfRange(SampleRate::from_hz(384_000)))
+        );
+        let reserved = PortConfig {
+            audio_format: AudioFormat::Reserved011,
+            ..master(PortClockSource::Mclk, PortMasterClockDivider::_512)
+        };
+        assert_eq!(
+            clocks.port_clocks(&reserved),
+            Err(ClockError::ReservedFormat(AudioFormat::Reserved011))
+        );
+    }
+
+    #[test]
+    fn dividers_for_common_crystals() {
+        let divider = |clock_hz, rate: Rate| {
+            PortMasterClockDivider::for_rate(clock_hz, rate.into()).map(|d| d.ratio())
+        };
+        assert_eq!(divider(22_579_200, Rate::Hz44100), Ok(512));
+        assert_eq!(divider(22_579_200, Rate::Hz176400), Ok(128));
+        assert_eq!(divider(12_288_000, Rate::Hz32000), Ok(384));
+        assert_eq!(divider(12_288_000, Rate::Hz96000), Ok(128));
+        assert!(divider(12_288_000, Rate::Hz192000).is_err());
+        assert!(divider(11_289_600, Rate::Hz48000).is_err());
+    }
+
+    #[test]
+    fn channel_status_codes() {
+        for rate in Rate::ALL {
+            assert_eq!(
+                Rate::from_channel_status_code(rate.channel_status_code()),
+                Some(rate)
+            );
+        }
+        assert_eq!(Rate::from_channel_status_code(0x1), None);
+        assert_eq!(Rate::from_channel_status_code(0xB), None);
+    }
+
+    #[test]
+    fn families() {
+        assert_eq!(Rate::Hz22050.family(), RateFamily::Hz44100);
+        assert_eq!(Rate::Hz768000.family(), RateFamily::Hz48000);
+        assert_eq!(Rate::Hz32000.family().base(), Rate::Hz32000);
+        for rate in Rate::ALL {
+            let (base, hz) = (rate.family().base().hz(), rate.hz());
+            let (high, low) = (base.max(hz), base.min(hz));
+            assert!(
+                high % low == 0 && (high / low).is_power_of_two(),
+                "{:?}",
+                rate
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_rate() {
+        assert_eq!(Rate::nearest(SampleRate::from_hz(22_000)), Rate::Hz22050);
+        assert_eq!(Rate::nearest(SampleRate::from_hz(47_950)), Rate::Hz48000);
+        assert_eq!(Rate::nearest(SampleRate::from_hz(500_000)), Rate::Hz768000);
+    }
+
+    #[test]
+    fn src_ratios() {
+        let ratio = |input: Rate, output: Rate| input.sample_rate().src_ratio(output.into());
+        assert_eq!(ratio(Rate::Hz96000, Rate::Hz48000).unwrap().to_bits(), 4096);
+        // 44.1 / 48 is 1881.6 steps.
+        assert_eq!(ratio(Rate::Hz44100, Rate::Hz48000).unwrap().to_bits(), 1882);
+        assert_eq!(ratio(Rate::Hz768000, Rate::Hz22050), None);
+        assert_eq!(
+            SampleRate::from_hz(48_000).src_ratio(SampleRate::from_hz(0)),
+            None
+        );
+    }
+
+    #[test]
+    fn transmitter_divider() {
+        let divider =
+            |clock_hz, rate: Rate| TransmitterClockDivider::for_rate(clock_hz, rate.into());
+        assert_eq!(
+            divider(24_576_000, Rate::Hz192000),
+            Ok(TransmitterClockDivider::_128)
+        );
+        assert_eq!(
+            divider(12_288_000, Rate::Hz32000),
+            Ok(TransmitterClockDivider::_384)
+        );
+        assert_eq!(
+            divider(24_576_000, Rate::Hz44100),
+            Err(ClockError::NoDivider {
+                clock_hz: 24_576_000,
+                rate: Rate::Hz44100.into()
+            })
+        );
+        assert_eq!(
+            TransmitterClockDivider::for_rate(49_152_000, SampleRate::from_hz(384_000)),
+            Err(ClockError::RateOutOfRange(SampleRate::from_hz(384_000)))
+        );
+    }
+
+    #[test]
+    fn recovered_clock() {
+        let rxcko = |rate: Rate, divider| ClockTree::recovered_clock_hz(rate.into(), divider);
+        assert_eq!(rxcko(Rate::Hz48000, RecoveredClockDivider::_1), 24_576_000);
+        assert_eq!(rxcko(Rate::Hz44100, RecoveredClockDivider::_2), 11_289_600);
+        assert_eq!(rxcko(Rate::Hz192000, RecoveredClockDivider::_8), 12_288_000);
+    }
+
+    #[test]
+    fn divider_for_rate() {
+        let rate = |hz| SampleRate::from_hz(hz);
+        assert_eq!(
+            PortMasterClockDivider::for_rate(24_576_000, rate(48_000)),
+            Ok(PortMasterClockDivider::_512)
+        );
+        assert_eq!(
+            PortMasterClockDivider::for_rate(24_576_000, rate(192_000)),
+            Ok(PortMasterClockDivider::_128)
+        );
+        assert_eq!(
+            PortMasterClockDivider::for_rate(18_432_000, rate(48_000)),
+            Ok(PortMasterClockDivider::_384)
+        );
+        assert_eq!(
+            PortMasterClockDivider::for_rate(24_576_000, rate(44_100)),
+            Err(ClockError::NoDivider {
+                clock_hz: 24_576_000,
+                rate: rate(44_100)
+            })
+        );
+        assert_eq!(
+            PortMasterClockDivider::for_rate(49_152_000, rate(384_000)),
+            Err(ClockError::RateOutOfRange(rate(384_000)))
+        );
+    }
+
+    #[test]
+    fn configure_port_for_rate_picks_the_divider() {
+        fn configure(sim: &SimulatedSrc4392, rate: Rate) -> Result<(), Error<Infallible>> {
+            let mut spi = sim;
+            let mut src4392 = device(sim);
+            src4392.set_clock_tree(ClockTree::new(24_576_000));
+            src4392.configure_port_for_rate(
+                &mut spi,
+                Port::B,
+                AudioFormat::I2S,
+                OutputDataSource::DIR,
+                rate,
+                PortClockSource::Mclk,
+            )
+        }
+
+        let sim = SimulatedSrc4392::new();
+        configure(&sim, Rate::Hz96000).unwrap();
+        assert_eq!(sim.register(Registers::PortBControl), 0b0010_1001);
+        assert_eq!(sim.register(Registers::PortBControl2), 0b0000_0001);
+
+        sim.set_register(Registers::PortBControl2, 0);
+        assert!(matches!(
+            configure(&sim, Rate::Hz44100),
+            Err(Error::Clock(ClockError::NoDivider { .. }))
+        ));
+        assert_eq!(sim.register(Registers::PortBControl2), 0);
+
+        let mut spi = &sim;
+        let result = device(&sim).configure_port_for_rate(
+            &mut spi,
+            Port::B,
+            AudioFormat::I2S,
+            OutputDataSource::DIR,
+            Rate::Hz48000,
+            PortClockSource::Mclk,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::Clock(ClockError::MissingClock(
+                PortClockSource::Mclk
+            )))
+        ));
+    }
+
+    #[test]
+    fn recovered_clock_checks() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        src4392.set_clock_tree(ClockTree::new(24_576_000));
+        src4392
+            .modify_register(&mut spi, |reg: &mut ReceiverInterruptMode2| {
+                reg.unlockm = InterruptMode::Level
+            })
+            .unwrap();
+        src4392.check_recovered_clock(&mut spi).unwrap();
+
+        src4392
+            .set_src(
+                &mut spi,
+                SrcSource::DIR,
+                SrcClockSource::Rxcko,
+                InterpolationFilterGroupDelay::_64,
+                Deemphasis::None,
+                false,
+            )
+            .unwrap();
+        assert!(matches!(
+            src4392.check_recovered_clock(&mut spi),
+            Err(Error::Clock(ClockError::RecoveredClockDisabled(
+                RxckoUser::Src
+            )))
+        ));
+
+        let rxcko_hz = src4392
+            .enable_recovered_clock(&mut spi, RecoveredClockDivider::_2, Rate::Hz48000.into())
+            .unwrap();
+        assert_eq!(rxcko_hz, 12_288_000);
+        assert_eq!(
+            sim.register(Registers::ReceiverControl) & 0b0001_1100,
+            0b0000_1100
+        );
+        assert_eq!(src4392.clock_tree().unwrap().rxcko_hz, Some(12_288_000));
+
+        src4392
+            .configure_port_for_rate(
+                &mut spi,
+                Port::A,
+                AudioFormat::I2S,
+                OutputDataSource::DIR,
+                Rate::Hz48000,
+                PortClockSource::Rxcko,
+            )
+            .unwrap();
+        assert!(matches!(
+            src4392.check_recovered_clock(&mut spi),
+            Err(Error::Clock(ClockError::ReceiverUnlocked(RxckoUser::Port(
+                Port::A
+            ))))
+        ));
+        sim.apply_event(Event::Lock { rate_hz: 48_000 });
+        src4392.check_recovered_clock(&mut spi).unwrap();
+
+        src4392.disable_recovered_clock(&mut spi).unwrap();
+        assert_eq!(src4392.clock_tree().unwrap().rxcko_hz, None);
+        assert!(matches!(
+            src4392.check_recovered_clock(&mut spi),
+            Err(Error::Clock(ClockError::RecoveredClockDisabled(
+                RxckoUser::Port(Port::A)
+            )))
+        ));
+    }
+
+    #[test]
+    fn recovered_clock_check_with_status_keeps_latched_flags() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        sim.apply_event(Event::Lock { rate_hz: 48_000 });
+        src4392
+            .modify_register(&mut spi, |reg: &mut ReceiverInterruptMask1| {
+                reg.munlock = true
+            })
+            .unwrap();
+        src4392
+            .enable_recovered_clock(&mut spi, RecoveredClockDivider::_2, Rate::Hz48000.into())
+            .unwrap();
+        src4392
+            .set_src(
+                &mut spi,
+                SrcSource::DIR,
+                SrcClockSource::Rxcko,
+                InterpolationFilterGroupDelay::_64,
+                Deemphasis::None,
+                false,
+            )
+            .unwrap();
+        sim.apply_event(Event::Unlock);
+        assert!(sim.interrupt_asserted());
+
+        let unlocked = ReceiverStatus1 {
+            unlock: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            src4392.check_recovered_clock_with_status(&mut spi, &unlocked),
+            Err(Error::Clock(ClockError::ReceiverUnlocked(RxckoUser::Src)))
+        ));
+        assert!(sim.interrupt_asserted());
+
+        assert!(matches!(
+            src4392.check_recovered_clock(&mut spi),
+            Err(Error::Clock(ClockError::ReceiverUnlocked(RxckoUser::Src)))
+        ));
+        assert!(!sim.interrupt_asserted());
+    }
+
+    #[test]
+    fn transmitter_for_rate_announces_the_rate() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        let mut buffers = [0u8; BUFFER_PAGE_LEN];
+        buffers[6] = 0xF2;
+        buffers[7] = 0x32;
+        let mut dump = [0u8; FULL_DUMP_LEN];
+        src4392.dump(&mut spi, &mut dump).unwrap();
+        dump[REGISTER_DUMP_LEN + BUFFER_PAGE_LEN..].copy_from_slice(&buffers);
+        src4392.restore(&mut spi, &dump).unwrap();
+
+        assert!(matches!(
+            src4392.configure_transmitter_for_rate(
+                &mut spi,
+                TransmitterClockSource::Rxcki,
+                Rate::Hz96000,
+                true
+            ),
+            Err(Error::Clock(ClockError::MissingTransmitterClock(
+                TransmitterClockSource::Rxcki
+            )))
+        ));
+        src4392.set_clock_tree(ClockTree::new(24_576_000).with_rxcki(22_579_200));
+        src4392
+            .configure_transmitter_for_rate(
+                &mut spi,
+                TransmitterClockSource::Rxcki,
+                Rate::Hz88200,
+                true,
+            )
+            .unwrap();
+        assert_eq!(sim.register(Registers::TransmitterControl), 0b0110_0000);
+        let buffers = sim.transmitter_buffers();
+        assert_eq!(buffers[6..8], [0xF8, 0x38]);
+        assert!(buffers[..6].iter().chain(&buffers[8..]).all(|b| *b == 0));
+
+        src4392
+            .configure_transmitter_for_rate(
+                &mut spi,
+                TransmitterClockSource::Mclk,
+                Rate::Hz48000,
+                false,
+            )
+            .unwrap();
+        assert_eq!(sim.register(Registers::TransmitterControl), 0b1100_0000);
+        assert_eq!(sim.transmitter_buffers()[6..8], [0xF8, 0x38]);
+    }
+}
@@ -0,0 +1,608 @@
+//! Falling back to a local clock when the receiver loses lock.
+//!
+//! A board can clock its ports and the SRC from the clock the DIR recovers from an external
+//! word clock or digital input, RXCKO, which stops when the DIR loses lock. A [`ClockFailover`]
+//! is polled on a schedule. It works through a priority list of clock sources and selects the
+//! first one that is running for every block it manages. The blocks are muted for each switch
+//! and the transition is reported to a callback. When the SRC is managed, it and the ports that
+//! output it are only unmuted once it reports ready on a later poll, since its output isn't valid
+//! until it has settled on the new clock. A poll reports [`FailoverEvent::SrcNotReady`] when the
+//! SRC still isn't ready after a number of polls, so the application can act on it.
+//!
+//! MCLK and RXCKI are free running inputs and are always taken as running, so only list the ones
+//! fitted on the board. RXCKO is running while the recovered clock output is enabled and the DIR
+//! is locked.
+//!
+//! ```
+//! # #[cfg(feature = "sim")]
+//! # fn main() {
+//! use src4392::clock_failover::{ClockFailover, ClockTransition, FailoverEvent};
+//! use src4392::receiver::RecoveredClockDivider;
+//! use src4392::sim::{Event, NoDelay, SimulatedSrc4392};
+//! use src4392::{
+//!     OutputDataSource, Port, PortAControl1Register, PortClockSource, Rate,
+//!     ReadModifyWriteSpiRegister, Src4392,
+//! };
+//!
+//! let sim = SimulatedSrc4392::new();
+//! let mut spi = &sim;
+//! let mut src4392 = Src4392::new(&sim, NoDelay);
+//! src4392
+//!     .enable_recovered_clock(&mut spi, RecoveredClockDivider::_2, Rate::Hz48000.into())
+//!     .unwrap();
+//! src4392
+//!     .modify_register(&mut spi, |reg: &mut PortAControl1Register| {
+//!         reg.aout = OutputDataSource::SRC
+//!     })
+//!     .unwrap();
+//!
+//! let mut last = None;
+//! let priorities = [PortClockSource::Rxcko, PortClockSource::Mclk];
+//! let mut failover = ClockFailover::new(&priorities, |transition: ClockTransition| {
+//!     last = Some(transition.to)
+//! })
+//! .with_port(Port::A)
+//! .with_src();
+//! failover.start(&mut src4392, &mut spi).unwrap();
+//!
+//! failover.poll(&mut src4392, &mut spi).unwrap();
+//! assert_eq!(failover.selected(), Some(PortClockSource::Mclk));
+//! sim.apply_event(Event::Lock { rate_hz: 48_000 });
+//! failover.poll(&mut src4392, &mut spi).unwrap();
+//! assert_eq!(failover.selected(), Some(PortClockSource::Rxcko));
+//! // The next poll unmutes the SRC and port A, which outputs it, once the SRC is ready.
+//! assert_eq!(
+//!     failover.poll(&mut src4392, &mut spi).unwrap(),
+//!     Some(FailoverEvent::SrcReady(PortClockSource::Rxcko))
+//! );
+//! assert_eq!(last, Some(PortClockSource::Rxcko));
+//! # }
+//! # #[cfg(not(feature = "sim"))]
+//! # fn main() {}
+//! ```
+
+use core::fmt::Debug;
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::spi::Transfer;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::interrupt::{InterruptMode, SrcAndDitInterruptMode, SrcAndDitStatus};
+use crate::port_control::{
+    OutputDataSource, PortAControl1Register, PortAControl2Register, PortBControl1Register,
+    PortBControl2Register, PortClockSource,
+};
+use crate::receiver::{ReceiverControl1, ReceiverInterruptMode2, ReceiverStatus1};
+use crate::sample_rate_converter::{SrcClockSource, SrcControl1};
+use crate::{Error, Port, ReadModifyWriteSpiRegister, Src4392};
+
+/// A switch from one clock source to another, reported by [`ClockFailover::poll`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClockTransition {
+    /// The source selected before, `None` for the first selection after
+    /// [`ClockFailover::start`].
+    pub from: Option<PortClockSource>,
+    pub to: PortClockSource,
+}
+
+/// What waiting for the SRC after a switch came to, reported by [`ClockFailover::poll`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FailoverEvent {
+    /// The SRC reported ready on the source. It and the ports that output it were unmuted.
+    SrcReady(PortClockSource),
+    /// The SRC wasn't ready after the number of polls set by
+    /// [`ClockFailover::with_ready_timeout`]. It and the ports that output it are still muted,
+    /// and later polls keep waiting.
+    SrcNotReady(PortClockSource),
+}
+
+/// The mutes of the SRC and the ports that output it from before a switch. `None` for ports that
+/// aren't waiting for the SRC.
+#[derive(Copy, Clone, Debug)]
+struct Mutes {
+    port_a: Option<bool>,
+    port_b: Option<bool>,
+    src: bool,
+}
+
+/// Selects the first running clock source from a priority list. See the [module docs](self).
+pub struct ClockFailover<'a, CALLBACK> {
+    priorities: &'a [PortClockSource],
+    on_transition: CALLBACK,
+    port_a: bool,
+    port_b: bool,
+    src: bool,
+    selected: Option<PortClockSource>,
+    /// Mutes to put back once the SRC is ready on the selected source.
+    pending_unmute: Option<Mutes>,
+    ready_timeout: u16,
+    /// Polls that found the SRC not ready since the last switch.
+    waited: u16,
+    /// The DIR unlock interrupt mode, and the SRC ready one when the SRC is managed, from before
+    /// [`ClockFailover::start`].
+    saved_modes: Option<(InterruptMode, Option<InterruptMode>)>,
+}
+
+impl<'a, CALLBACK: FnMut(ClockTransition)> ClockFailover<'a, CALLBACK> {
+    /// A failover trying `priorities` in order, managing no blocks yet. `on_transition` is
+    /// called after every switch. The SRC is reported not ready 10 polls after a switch.
+    pub fn new(priorities: &'a [PortClockSource], on_transition: CALLBACK) -> Self {
+        ClockFailover {
+            priorities,
+            on_transition,
+            port_a: false,
+            port_b: false,
+            src: false,
+            selected: None,
+            pending_unmute: None,
+            ready_timeout: 10,
+            waited: 0,
+            saved_modes: None,
+        }
+    }
+
+    /// Reports [`FailoverEvent::SrcNotReady`] when the SRC isn't ready `polls` polls after a
+    /// switch.
+    ///
+    /// # Panics
+    ///
+    /// When `polls` is 0.
+    pub fn with_ready_timeout(mut self, polls: u16) -> Self {
+        assert!(polls > 0, "the ready timeout needs at least one poll");
+        self.ready_timeout = polls;
+        self
+    }
+
+    /// Also switches the clock source of `port`.
+    pub fn with_port(mut self, port: Port) -> Self {
+        match port {
+            Port::A => self.port_a = true,
+            Port::B => self.port_b = true,
+        }
+        self
+    }
+
+    /// Also switches the clock source of the SRC.
+    pub fn with_src(mut self) -> Self {
+        self.src = true;
+        self
+    }
+
+    /// The source selected by the last switch.
+    pub fn selected(&self) -> Option<PortClockSource> {
+        self.selected
+    }
+
+    /// Puts the DIR unlock status bit, and the SRC ready status bit when the SRC is managed, in
+    /// level mode so each poll sees the current state rather than a latched edge. Their previous
+    /// modes are restored by [`ClockFailover::stop`]. The next poll selects a source whatever the
+    /// blocks are set to.
+    pub fn start<P, SPI, E, D, DT>(
+        &mut self,
+        src4392: &mut Src4392<P, SPI, E, D, DT>,
+        spi: &mut SPI,
+    ) -> Result<(), Error<E>>
+    where
+        P: OutputPin,
+        P::Error: Debug,
+        SPI: Transfer<u8, Error = E>,
+        D: DelayMs<DT>,
+        DT: From<u8>,
+    {
+        let mut unlockm = InterruptMode::Level;
+        src4392.modify_register(spi, |reg: &mut ReceiverInterruptMode2| {
+            unlockm = core::mem::replace(&mut reg.unlockm, InterruptMode::Level);
+        })?;
+        let mut readym = None;
+        if self.src {
+            src4392.modify_register(spi, |reg: &mut SrcAndDitInterruptMode| {
+                readym = Some(core::mem::replace(&mut reg.readym, InterruptMode::Level));
+            })?;
+        }
+        // Starting again mustn't save the level modes set by the first start.
+        self.saved_modes.get_or_insert((unlockm, readym));
+        self.selected = None;
+        Ok(())
+    }
+
+    /// Stops switching and puts the DIR unlock and SRC ready interrupt modes back as they were
+    /// before [`ClockFailover::start`]. The clock sources and mutes are left as they are.
+    pub fn stop<P, SPI, E, D, DT>(
+        &mut self,
+        src4392: &mut Src4392<P, SPI, E, D, DT>,
+        spi: &mut SPI,
+    ) -> Result<(), Error<E>>
+    where
+        P: OutputPin,
+        P::Error: Debug,
+        SPI: Transfer<u8, Error = E>,
+        D: DelayMs<DT>,
+        DT: From<u8>,
+    {
+        if let Some((unlockm, readym)) = self.saved_modes {
+            src4392.modify_register(spi, |reg: &mut ReceiverInterruptMode2| {
+                reg.unlockm = unlockm
+            })?;
+            if let Some(readym) = readym {
+                src4392
+                    .modify_register(spi, |reg: &mut SrcAndDitInterruptMode| reg.readym = readym)?;
+            }
+            self.saved_modes = None;
+        }
+        self.selected = None;
+        self.pending_unmute = None;
+        Ok(())
+    }
+
+    /// Checks which sources are running and switches the managed blocks to the first one in the
+    /// priority list, if that isn't the source selected already. Nothing changes when none of
+    /// the sources is running.
+    ///
+    /// The SRC and the ports that output it stay muted after a switch until a poll finds the SRC
+    /// ready, which is reported as [`FailoverEvent::SrcReady`]. Other ports have nothing to wait
+    /// for and are unmuted straight away. Returns [`FailoverEvent::SrcNotReady`] once when the
+    /// wait passes the timeout.
+    pub fn poll<P, SPI, E, D, DT>(
+        &mut self,
+        src4392: &mut Src4392<P, SPI, E, D, DT>,
+        spi: &mut SPI,
+    ) -> Result<Option<FailoverEvent>, Error<E>>
+    where
+        P: OutputPin,
+        P::Error: Debug,
+        SPI: Transfer<u8, Error = E>,
+        D: DelayMs<DT>,
+        DT: From<u8>,
+    {
+        let rxcko_running = if self.priorities.contains(&PortClockSource::Rxcko) {
+            let control: ReceiverControl1 = src4392.read_register(spi)?;
+            control.rxckoe && {
+                let status: ReceiverStatus1 = src4392.read_register(spi)?;
+                !status.unlock
+            }
+        } else {
+            false
+        };
+        let Some(source) = self.priorities.iter().copied().find(|source| match source {
+            PortClockSource::Mclk | PortClockSource::Rxcki => true,
+            PortClockSource::Rxcko => rxcko_running,
+            PortClockSource::Reserved => false,
+        }) else {
+            return self.unmute_when_ready(src4392, spi);
+        };
+        if self.selected == Some(source) {
+            return self.unmute_when_ready(src4392, spi);
+        }
+
+        self.switch(src4392, spi, source)?;
+        let transition = ClockTransition {
+            from: self.selected,
+            to: source,
+        };
+        self.selected = Some(source);
+        (self.on_transition)(transition);
+        Ok(None)
+    }
+
+    /// Mutes the managed blocks and switches their clock source. Ports that don't output the SRC
+    /// are unmuted again straight away. The mutes from before of the SRC and the ports that
+    /// output it are kept for [`ClockFailover::unmute_when_ready`], unless an earlier switch is
+    /// still waiting.
+    fn switch<P, SPI, E, D, DT>(
+        &mut self,
+        src4392: &mut Src4392<P, SPI, E, D, DT>,
+        spi: &mut SPI,
+        source: PortClockSource,
+    ) -> Result<(), Error<E>>
+    where
+        P: OutputPin,
+        P::Error: Debug,
+        SPI: Transfer<u8, Error = E>,
+        D: DelayMs<DT>,
+        DT: From<u8>,
+    {
+        let src_source = match source {
+            PortClockSource::Mclk => SrcClockSource::Mclk,
+            PortClockSource::Rxcki => SrcClockSource::Rxcki,
+            PortClockSource::Rxcko => SrcClockSource::Rxcko,
+            PortClockSource::Reserved => SrcClockSource::Reserved,
+        };
+        let (mut port_a_muted, mut port_b_muted, mut src_muted) = (false, false, false);
+        let (mut port_a_waits, mut port_b_waits) = (false, false);
+
+        if self.port_a {
+            src4392.modify_register(spi, |reg: &mut PortAControl1Register| {
+                port_a_muted = reg.amute;
+                port_a_waits = self.src && reg.aout == OutputDataSource::SRC;
+                reg.amute = true;
+            })?;
+        }
+        if self.port_b {
+            src4392.modify_register(spi, |reg: &mut PortBControl1Register| {
+                port_b_muted = reg.amute;
+                port_b_waits = self.src && reg.aout == OutputDataSource::SRC;
+                reg.amute = true;
+            })?;
+        }
+        if self.src {
+            src4392.modify_register(spi, |reg: &mut SrcControl1| {
+                src_muted = reg.mute;
+                reg.mute = true;
+            })?;
+        }
+
+        if self.port_a {
+            src4392.modify_register(spi, |reg: &mut PortAControl2Register| reg.aclk = source)?;
+        }
+        if self.port_b {
+            src4392.modify_register(spi, |reg: &mut PortBControl2Register| reg.aclk = source)?;
+        }
+        if self.src {
+            src4392.modify_register(spi, |reg: &mut SrcControl1| reg.clock_source = src_source)?;
+        }
+
+        if self.port_a && !port_a_waits {
+            src4392.modify_register(spi, |reg: &mut PortAControl1Register| {
+                reg.amute = port_a_muted
+            })?;
+        }
+        if self.port_b && !port_b_waits {
+            src4392.modify_register(spi, |reg: &mut PortBControl1Register| {
+                reg.amute = port_b_muted
+            })?;
+        }
+
+        if self.src {
+            let pending = self.pending_unmute.get_or_insert(Mutes {
+                port_a: None,
+                port_b: None,
+                src: src_muted,
+            });
+            if port_a_waits {
+                pending.port_a.get_or_insert(port_a_muted);
+            }
+            if port_b_waits {
+                pending.port_b.get_or_insert(port_b_muted);
+            }
+        }
+        self.waited = 0;
+        Ok(())
+    }
+
+    /// Puts back the mutes of the SRC and the ports that output it from before the last switch
+    /// once the SRC is ready. The SRC is unmuted before the ports.
+    fn unmute_when_ready<P, SPI, E, D, DT>(
+        &mut self,
+        src4392: &mut Src4392<P, SPI, E, D, DT>,
+        spi: &mut SPI,
+    ) -> Result<Option<FailoverEvent>, Error<E>>
+    where
+        P: OutputPin,
+        P::Error: Debug,
+        SPI: Transfer<u8, Error = E>,
+        D: DelayMs<DT>,
+        DT: From<u8>,
+    {
+        let Some(mutes) = self.pending_unmute else {
+            return Ok(None);
+        };
+        let status: SrcAndDitStatus = src4392.read_register(spi)?;
+        if !status.ready {
+            self.waited = self.waited.saturating_add(1);
+            if self.waited == self.ready_timeout {
+                return Ok(self.selected.map(FailoverEvent::SrcNotReady));
+            }
+            return Ok(None);
+        }
+        src4392.modify_register(spi, |reg: &mut SrcControl1| reg.mute = mutes.src)?;
+        if let Some(muted) = mutes.port_a {
+            src4392.modify_register(spi, |reg: &mut PortAControl1Register| reg.amute = muted)?;
+        }
+        if let Some(muted) = mutes.port_b {
+            src4392.modify_register(spi, |reg: &mut PortBControl1Register| reg.amute = muted)?;
+        }
+        self.pending_unmute = None;
+        Ok(self.selected.map(FailoverEvent::SrcReady))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receiver::RecoveredClockDivider;
+    use crate::registers::Registers;
+    use crate::sim::fixture::device;
+    use crate::sim::{Event, SimulatedSrc4392};
+    use crate::Rate;
+
+    const SRC_MUTE: u8 = 0b0001_0000;
+    const PORT_MUTE: u8 = 0b0100_0000;
+    const PORT_A_SRC: u8 = 0b0011_0000;
+
+    #[test]
+    fn falls_back_and_returns() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        src4392
+            .enable_recovered_clock(&mut spi, RecoveredClockDivider::_1, Rate::Hz48000.into())
+            .unwrap();
+        sim.apply_event(Event::Lock { rate_hz: 48_000 });
+        // Port A outputs the SRC. Port B is muted by the application and has to stay muted.
+        sim.set_register(Registers::PortAControl, PORT_A_SRC);
+        sim.set_register(Registers::PortBControl, PORT_MUTE);
+
+        let mut transitions = [None; 4];
+        let mut count = 0;
+        let priorities = [PortClockSource::Rxcko, PortClockSource::Rxcki];
+        let mut failover = ClockFailover::new(&priorities, |transition| {
+            transitions[count] = Some(transition);
+            count += 1;
+        })
+        .with_port(Port::A)
+        .with_port(Port::B)
+        .with_src()
+        .with_ready_timeout(2);
+        failover.start(&mut src4392, &mut spi).unwrap();
+
+        let muted = |sim: &SimulatedSrc4392| {
+            (
+                sim.register(Registers::PortAControl) & PORT_MUTE != 0,
+                sim.register(Registers::PortBControl) & PORT_MUTE != 0,
+                sim.register(Registers::SrcControl) & SRC_MUTE != 0,
+            )
+        };
+
+        failover.poll(&mut src4392, &mut spi).unwrap();
+        assert_eq!(sim.register(Registers::PortAControl2), 0b0000_1000);
+        assert_eq!(sim.register(Registers::PortBControl2), 0b0000_1000);
+        assert_eq!(
+            sim.register(Registers::SrcControl) & 0b0000_1100,
+            0b0000_1000
+        );
+        assert_eq!(muted(&sim), (true, true, true));
+        assert_eq!(
+            failover.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FailoverEvent::SrcReady(PortClockSource::Rxcko))
+        );
+        assert_eq!(muted(&sim), (false, true, false));
+
+        sim.apply_event(Event::Unlock);
+        assert_eq!(failover.poll(&mut src4392, &mut spi).unwrap(), None);
+        assert_eq!(failover.selected(), Some(PortClockSource::Rxcki));
+        assert_eq!(sim.register(Registers::PortAControl2), 0b0000_0100);
+        assert_eq!(
+            sim.register(Registers::SrcControl) & 0b0000_1100,
+            0b0000_0100
+        );
+        // The SRC input went with the lock, so it never gets ready and everything stays muted.
+        assert_eq!(failover.poll(&mut src4392, &mut spi).unwrap(), None);
+        assert_eq!(
+            failover.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FailoverEvent::SrcNotReady(PortClockSource::Rxcki))
+        );
+        assert_eq!(failover.poll(&mut src4392, &mut spi).unwrap(), None);
+        assert_eq!(muted(&sim), (true, true, true));
+
+        sim.apply_event(Event::Lock { rate_hz: 44_100 });
+        assert_eq!(failover.poll(&mut src4392, &mut spi).unwrap(), None);
+        assert_eq!(muted(&sim), (true, true, true));
+        assert_eq!(
+            failover.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FailoverEvent::SrcReady(PortClockSource::Rxcko))
+        );
+        assert_eq!(muted(&sim), (false, true, false));
+
+        assert_eq!(count, 3);
+        assert_eq!(
+            transitions[..3],
+            [
+                Some(ClockTransition {
+                    from: None,
+                    to: PortClockSource::Rxcko
+                }),
+                Some(ClockTransition {
+                    from: Some(PortClockSource::Rxcko),
+                    to: PortClockSource::Rxcki
+                }),
+                Some(ClockTransition {
+                    from: Some(PortClockSource::Rxcki),
+                    to: PortClockSource::Rxcko
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled_recovered_clock_is_skipped() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        sim.apply_event(Event::Lock { rate_hz: 48_000 });
+
+        let priorities = [PortClockSource::Rxcko];
+        let mut failover = ClockFailover::new(&priorities, |_| {}).with_src();
+        failover.start(&mut src4392, &mut spi).unwrap();
+        failover.poll(&mut src4392, &mut spi).unwrap();
+        assert_eq!(failover.selected(), None);
+        assert_eq!(sim.register(Registers::SrcControl), 0);
+    }
+
+    #[test]
+    fn ports_alone_unmute_straight_away() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        let priorities = [PortClockSource::Rxcki];
+        let mut failover = ClockFailover::new(&priorities, |_| {}).with_port(Port::A);
+        failover.start(&mut src4392, &mut spi).unwrap();
+        failover.poll(&mut src4392, &mut spi).unwrap();
+        assert_eq!(sim.register(Registers::PortAControl2), 0b0000_0100);
+        assert_eq!(sim.register(Registers::PortAControl) & PORT_MUTE, 0);
+    }
+
+    #[test]
+    fn only_ports_outputting_the_src_wait_for_it() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        sim.set_register(Registers::PortAControl, PORT_A_SRC);
+
+        let priorities = [PortClockSource::Rxcki];
+        let mut failover = ClockFailover::new(&priorities, |_| {})
+            .with_port(Port::A)
+            .with_port(Port::B)
+            .with_src()
+            .with_ready_timeout(1);
+        failover.start(&mut src4392, &mut spi).unwrap();
+        assert_eq!(failover.poll(&mut src4392, &mut spi).unwrap(), None);
+        assert_ne!(sim.register(Registers::PortAControl) & PORT_MUTE, 0);
+        assert_eq!(sim.register(Registers::PortBControl) & PORT_MUTE, 0);
+        assert_eq!(
+            failover.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FailoverEvent::SrcNotReady(PortClockSource::Rxcki))
+        );
+
+        sim.apply_event(Event::Lock { rate_hz: 48_000 });
+        assert_eq!(
+            failover.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FailoverEvent::SrcReady(PortClockSource::Rxcki))
+        );
+        assert_eq!(sim.register(Registers::PortAControl) & PORT_MUTE, 0);
+        assert_eq!(sim.register(Registers::SrcControl) & SRC_MUTE, 0);
+    }
+
+    #[test]
+    fn stop_restores_interrupt_modes() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        src4392
+            .modify_register(&mut spi, |reg: &mut ReceiverInterruptMode2| {
+                reg.unlockm = InterruptMode::FallingEdge
+            })
+            .unwrap();
+        src4392
+            .modify_register(&mut spi, |reg: &mut SrcAndDitInterruptMode| {
+                reg.readym = InterruptMode::RisingEdge
+            })
+            .unwrap();
+        let modes = |sim: &SimulatedSrc4392| {
+            (
+                sim.register(Registers::ReceiverInterruptMode2),
+                sim.register(Registers::SrcAndDitInterruptMode),
+            )
+        };
+        let before = modes(&sim);
+
+        let priorities = [PortClockSource::Mclk];
+        let mut failover = ClockFailover::new(&priorities, |_| {}).with_src();
+        failover.start(&mut src4392, &mut spi).unwrap();
+        failover.start(&mut src4392, &mut spi).unwrap();
+        assert_ne!(modes(&sim).0, before.0);
+        assert_ne!(modes(&sim).1, before.1);
+        failover.stop(&mut src4392, &mut spi).unwrap();
+        assert_eq!(modes(&sim), before);
+    }
+}
@@ -0,0 +1,1053 @@
+use packed_struct::prelude::*;
+
+use crate::gpo::{Gpo, GpoFunction, GpoRegister};
+use crate::port_control::{
+    AudioFormat, OutputDataSource, PortAControl1Register, PortAControl2Register,
+    PortBControl1Register, PortBControl2Register, PortClockSource, PortMasterClockDivider,
+};
+use crate::receiver::{
+    ReceiverControl1, ReceiverControl2, ReceiverInput, ReceiverPllConfiguration,
+    ReceiverReferenceClock, RecoveredClockDivider,
+};
+use crate::registers::Registers;
+use crate::reset::Reset;
+use crate::sample_rate_converter::{
+    Deemphasis, InterpolationFilterGroupDelay, SrcClockSource, SrcControl1, SrcControl2,
+    SrcControl3, SrcControl4, SrcControl5, SrcOutputWordLength, SrcSource,
+};
+use crate::transmitter::{
+    BypassMultiplexer, TransmitterClockDivider, TransmitterClockSource, TransmitterControl1,
+    TransmitterControl2, TransmitterInputSource,
+};
+use crate::{ClockTree, Port};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The SRC output is sent back out of the same port that feeds the SRC.
+    RoutingLoop(Port),
+    /// Direct down sampling is only valid when the output rate is higher than the input rate.
+    /// [`SrcConfig::nominal_rates`] must be set to prove that.
+    DirectDownSamplingWithoutUpsampling,
+    /// A master port is clocked from RXCKO but the recovered clock output isn't running, or from
+    /// RXCKI on a board without it.
+    MissingPortClock(Port),
+    /// The SRC is clocked from RXCKO but the recovered clock output isn't running, or from RXCKI
+    /// on a board without it.
+    MissingSrcClock,
+    /// The powered up DIT is clocked from RXCKI on a board without it.
+    MissingTransmitterClock,
+    /// A port outputs data from a block that is powered down.
+    OutputSourcePoweredDown(Port),
+    /// The SRC takes its input from a block that is powered down.
+    SrcSourcePoweredDown,
+    /// A field of the register is set to a code the datasheet reserves.
+    ReservedSetting(Registers),
+}
+
+/// A register held a reserved code that doesn't map to any setting.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub register: Registers,
+    pub value: u8,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct PortConfig {
+    pub audio_format: AudioFormat,
+    pub output_data_source: OutputDataSource,
+    pub clock_divider: PortMasterClockDivider,
+    pub clock_source: PortClockSource,
+    pub is_master: bool,
+    pub mute: bool,
+}
+
+impl PortConfig {
+    /// The reset state of a port.
+    pub const RESET: PortConfig = PortConfig {
+        audio_format: AudioFormat::LeftJustified24bit,
+        output_data_source: OutputDataSource::Loopback,
+        clock_divider: PortMasterClockDivider::_128,
+        clock_source: PortClockSource::Mclk,
+        is_master: false,
+        mute: false,
+    };
+}
+
+impl Default for PortConfig {
+    fn default() -> Self {
+        Self::RESET
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct SrcConfig {
+    pub source: SrcSource,
+    pub clock_source: SrcClockSource,
+    pub interpolation_group_delay: InterpolationFilterGroupDelay,
+    pub deemphasis: Deemphasis,
+    pub auto_deemphasis: bool,
+    pub direct_down_sampling: bool,
+    pub track: bool,
+    pub mute: bool,
+    pub output_word_length: SrcOutputWordLength,
+    pub attenuation_left: u8,
+    pub attenuation_right: u8,
+    /// The expected input and output sample rates in Hz. This isn't written to the device, it is
+    /// only used to check that direct down sampling is safe.
+    pub nominal_rates: Option<(u32, u32)>,
+}
+
+impl SrcConfig {
+    /// The reset state of the SRC.
+    pub const RESET: SrcConfig = SrcConfig {
+        source: SrcSource::PortA,
+        clock_source: SrcClockSource::Mclk,
+        interpolation_group_delay: InterpolationFilterGroupDelay::_64,
+        deemphasis: Deemphasis::None,
+        auto_deemphasis: false,
+        direct_down_sampling: false,
+        track: false,
+        mute: false,
+        output_word_length: SrcOutputWordLength::_24bits,
+        attenuation_left: 0,
+        attenuation_right: 0,
+        nominal_rates: None,
+    };
+}
+
+impl Default for SrcConfig {
+    fn default() -> Self {
+        Self::RESET
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct PllConfig {
+    pub p: u8,
+    pub j: u8,
+    pub d: u16,
+}
+
+impl PllConfig {
+    pub const RESET: PllConfig = PllConfig { p: 0, j: 0, d: 0 };
+}
+
+impl Default for PllConfig {
+    fn default() -> Self {
+        Self::RESET
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct ReceiverConfig {
+    pub input: ReceiverInput,
+    pub reference_clock: ReceiverReferenceClock,
+    pub recovered_clock_output: bool,
+    pub recovered_clock_divider: RecoveredClockDivider,
+    pub auto_mute_on_loss_of_lock: bool,
+    pub zero_on_loss_of_lock: bool,
+    /// Stops the DIR channel status and user data buffers updating, RXBTD.
+    pub buffer_transfer_disabled: bool,
+    pub pll: PllConfig,
+}
+
+impl ReceiverConfig {
+    /// The reset state of the receiver.
+    pub const RESET: ReceiverConfig = ReceiverConfig {
+        input: ReceiverInput::Rx1,
+        reference_clock: ReceiverReferenceClock::Rxcki,
+        recovered_clock_output: false,
+        recovered_clock_divider: RecoveredClockDivider::_1,
+        auto_mute_on_loss_of_lock: false,
+        zero_on_loss_of_lock: false,
+        buffer_transfer_disabled: false,
+        pll: PllConfig::RESET,
+    };
+}
+
+impl Default for ReceiverConfig {
+    fn default() -> Self {
+        Self::RESET
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct TransmitterConfig {
+    pub input: TransmitterInputSource,
+    pub clock_source: TransmitterClockSource,
+    pub clock_divider: TransmitterClockDivider,
+    pub validity: bool,
+    pub mute: bool,
+    pub line_driver_off: bool,
+    pub aes_output_off: bool,
+    pub line_driver_bypass: bool,
+    pub aes_output_bypass: bool,
+    pub bypass_input: BypassMultiplexer,
+    /// The block start pin is an input, BSSL.
+    pub block_start_input: bool,
+    /// BLSM.
+    pub block_start_mode: bool,
+    /// Stops the DIT channel status and user data buffers being loaded into the transmitter,
+    /// TXBTD.
+    pub buffer_transfer_disabled: bool,
+}
+
+impl TransmitterConfig {
+    /// The reset state of the transmitter.
+    pub const RESET: TransmitterConfig = TransmitterConfig {
+        input: TransmitterInputSource::PortA,
+        clock_source: TransmitterClockSource::Mclk,
+        clock_divider: TransmitterClockDivider::_128,
+        validity: false,
+        mute: false,
+        line_driver_off: false,
+        aes_output_off: false,
+        line_driver_bypass: false,
+        aes_output_bypass: false,
+        bypass_input: BypassMultiplexer::Rx1,
+        block_start_input: false,
+        block_start_mode: false,
+        buffer_transfer_disabled: false,
+    };
+}
+
+impl Default for TransmitterConfig {
+    fn default() -> Self {
+        Self::RESET
+    }
+}
+
+/// Which functional blocks are powered up. The default is the reset state, everything powered
+/// down.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct PowerConfig {
+    pub port_a: bool,
+    pub port_b: bool,
+    pub transmitter: bool,
+    pub receiver: bool,
+    pub src: bool,
+}
+
+impl PowerConfig {
+    pub const RESET: PowerConfig = PowerConfig {
+        port_a: false,
+        port_b: false,
+        transmitter: false,
+        receiver: false,
+        src: false,
+    };
+
+    pub const ALL_ON: PowerConfig = PowerConfig {
+        port_a: true,
+        port_b: true,
+        transmitter: true,
+        receiver: true,
+        src: true,
+    };
+
+    const fn any(&self) -> bool {
+        self.port_a || self.port_b || self.transmitter || self.receiver || self.src
+    }
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self::RESET
+    }
+}
+
+/// A complete description of the device setup. The default value matches the state of the device
+/// after a reset.
+///
+/// Build one with [`DeviceConfig::builder`] and write it with [`crate::Src4392::apply`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct DeviceConfig {
+    pub port_a: PortConfig,
+    pub port_b: PortConfig,
+    pub src: SrcConfig,
+    pub receiver: ReceiverConfig,
+    pub transmitter: TransmitterConfig,
+    pub gpo: [GpoFunction; 4],
+    pub power: PowerConfig,
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        Self::RESET
+    }
+}
+
+impl DeviceConfig {
+    /// The state of the device after a reset.
+    pub const RESET: DeviceConfig = DeviceConfig {
+        port_a: PortConfig::RESET,
+        port_b: PortConfig::RESET,
+        src: SrcConfig::RESET,
+        receiver: ReceiverConfig::RESET,
+        transmitter: TransmitterConfig::RESET,
+        gpo: [GpoFunction::Low; 4],
+        power: PowerConfig::RESET,
+    };
+
+    /// The registers covered by a [`DeviceConfig`], in the order they are written.
+    pub const REGISTERS: [Registers; 21] = [
+        Registers::Reset,
+        Registers::ReceiverControl,
+        Registers::ReceiverControl2,
+        Registers::ReceiverPllConfiguration,
+        Registers::ReceiverPllConfiguration2,
+        Registers::ReceiverPllConfiguration3,
+        Registers::SrcControl,
+        Registers::SrcControl2,
+        Registers::SrcControl3,
+        Registers::SrcControl4,
+        Registers::SrcControl5,
+        Registers::PortAControl,
+        Registers::PortAControl2,
+        Registers::PortBControl,
+        Registers::PortBControl2,
+        Registers::TransmitterControl,
+        Registers::TransmitterControl2,
+        Registers::Gpo1,
+        Registers::Gpo2,
+        Registers::Gpo3,
+        Registers::Gpo4,
+    ];
+
+    /// Registers holding a mute bit, and the mask of that bit.
+    pub(crate) const MUTES: [(Registers, u8); 4] = [
+        (Registers::PortAControl, 0b0100_0000),
+        (Registers::PortBControl, 0b0100_0000),
+        (Registers::SrcControl, 0b0001_0000),
+        (Registers::TransmitterControl2, 0b0000_0010),
+    ];
+
+    pub const fn builder() -> DeviceConfigBuilder {
+        DeviceConfigBuilder::new()
+    }
+
+    pub const fn port(&self, port: Port) -> &PortConfig {
+        match port {
+            Port::A => &self.port_a,
+            Port::B => &self.port_b,
+        }
+    }
+
+    /// Checks that the device can run this setup. This is a `const fn`, so a config built in
+    /// `const` context is checked at compile time.
+    ///
+    /// Which clock inputs are fitted is board specific, so RXCKI is taken to be present here.
+    /// [`DeviceConfig::validate_clocks`] checks the clock sources against a [`ClockTree`].
+    pub const fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(register) = self.reserved_setting() {
+            return Err(ConfigError::ReservedSetting(register));
+        }
+        if let Err(error) = self.validate_port(Port::A) {
+            return Err(error);
+        }
+        if let Err(error) = self.validate_port(Port::B) {
+            return Err(error);
+        }
+
+        if matches!(self.src.clock_source, SrcClockSource::Rxcko) && !self.rxcko_running() {
+            return Err(ConfigError::MissingSrcClock);
+        }
+
+        let src_source_powered = match self.src.source {
+            SrcSource::PortA => self.power.port_a,
+            SrcSource::PortB => self.power.port_b,
+            SrcSource::DIR => self.power.receiver,
+            SrcSource::Reserved => false,
+        };
+        if self.power.src && !src_source_powered {
+            return Err(ConfigError::SrcSourcePoweredDown);
+        }
+
+        if self.src.direct_down_sampling {
+            match self.src.nominal_rates {
+                Some((input, output)) if output > input => {}
+                _ => return Err(ConfigError::DirectDownSamplingWithoutUpsampling),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every clock source the setup uses is in `clocks`, as well as
+    /// [`DeviceConfig::validate`]. RXCKO is taken from the setup, not from `clocks`, since its
+    /// frequency depends on the input.
+    pub const fn validate_clocks(&self, clocks: &ClockTree) -> Result<(), ConfigError> {
+        if let Err(error) = self.validate() {
+            return Err(error);
+        }
+        let rxcki = clocks.rxcki_hz.is_some();
+        if self.port_a.is_master
+            && matches!(self.port_a.clock_source, PortClockSource::Rxcki)
+            && !rxcki
+        {
+            return Err(ConfigError::MissingPortClock(Port::A));
+        }
+        if self.port_b.is_master
+            && matches!(self.port_b.clock_source, PortClockSource::Rxcki)
+            && !rxcki
+        {
+            return Err(ConfigError::MissingPortClock(Port::B));
+        }
+        if matches!(self.src.clock_source, SrcClockSource::Rxcki) && !rxcki {
+            return Err(ConfigError::MissingSrcClock);
+        }
+        if self.power.transmitter
+            && matches!(self.transmitter.clock_source, TransmitterClockSource::Rxcki)
+            && !rxcki
+        {
+            return Err(ConfigError::MissingTransmitterClock);
+        }
+        Ok(())
+    }
+
+    const fn rxcko_running(&self) -> bool {
+        self.power.receiver && self.receiver.recovered_clock_output
+    }
+
+    const fn validate_port(&self, port: Port) -> Result<(), ConfigError> {
+        let config = self.port(port);
+        let src_feeds_from_port = matches!(
+            (self.src.source, port),
+            (SrcSource::PortA, Port::A) | (SrcSource::PortB, Port::B)
+        );
+        if src_feeds_from_port && matches!(config.output_data_source, OutputDataSource::SRC) {
+            return Err(ConfigError::RoutingLoop(port));
+        }
+        if config.is_master
+            && matches!(config.clock_source, PortClockSource::Rxcko)
+            && !self.rxcko_running()
+        {
+            return Err(ConfigError::MissingPortClock(port));
+        }
+        let source_powered = match config.output_data_source {
+            OutputDataSource::SRC => self.power.src,
+            OutputDataSource::DIR => self.power.receiver,
+            OutputDataSource::Loopback | OutputDataSource::OtherPort => true,
+        };
+        if !source_powered {
+            return Err(ConfigError::OutputSourcePoweredDown(port));
+        }
+        Ok(())
+    }
+
+    /// The first register, in the order of [`DeviceConfig::REGISTERS`], with a field set to a
+    /// reserved code.
+    const fn reserved_setting(&self) -> Option<Registers> {
+        const fn reserved_format(format: AudioFormat) -> bool {
+            matches!(format, AudioFormat::Reserved010 | AudioFormat::Reserved011)
+        }
+        if matches!(self.src.source, SrcSource::Reserved)
+            || matches!(self.src.clock_source, SrcClockSource::Reserved)
+        {
+            Some(Registers::SrcControl)
+        } else if reserved_format(self.port_a.audio_format) {
+            Some(Registers::PortAControl)
+        } else if matches!(self.port_a.clock_source, PortClockSource::Reserved) {
+            Some(Registers::PortAControl2)
+        } else if reserved_format(self.port_b.audio_format) {
+            Some(Registers::PortBControl)
+        } else if matches!(self.port_b.clock_source, PortClockSource::Reserved) {
+            Some(Registers::PortBControl2)
+        } else if matches!(self.transmitter.input, TransmitterInputSource::Reserved) {
+            Some(Registers::TransmitterControl)
+        } else {
+            None
+        }
+    }
+
+    /// Packs the configuration into register values, in the order of [`DeviceConfig::REGISTERS`].
+    ///
+    /// This is a `const fn`, so the image of a `const` config can be built at compile time and
+    /// written with [`crate::Src4392::load_image`]. The packed register structs can't be built in
+    /// `const` context, so the bit layout is repeated here; the tests check it against them.
+    pub const fn to_image(&self) -> [u8; 21] {
+        const fn bit(value: bool, position: u8) -> u8 {
+            (value as u8) << position
+        }
+
+        let power = &self.power;
+        let reset = bit(power.src, 0)
+            | bit(power.receiver, 1)
+            | bit(power.transmitter, 2)
+            | bit(power.port_b, 3)
+            | bit(power.port_a, 4)
+            | bit(power.any(), 5);
+
+        let receiver = &self.receiver;
+        let receiver1 = receiver.input as u8
+            | bit(receiver.recovered_clock_output, 2)
+            | (receiver.recovered_clock_divider as u8) << 3
+            | (receiver.reference_clock as u8) << 5
+            | bit(receiver.buffer_transfer_disabled, 6);
+        let receiver2 =
+            bit(receiver.auto_mute_on_loss_of_lock, 0) | bit(receiver.zero_on_loss_of_lock, 1);
+        let pll = &receiver.pll;
+        let pll1 = (pll.p & 0x0F) << 4 | (pll.j & 0x3F) >> 2;
+        let pll2 = (pll.j & 0x03) << 6 | ((pll.d >> 8) & 0x3F) as u8;
+        let pll3 = pll.d as u8;
+
+        let src = &self.src;
+        let src1 =
+            src.source as u8 | (src.clock_source as u8) << 2 | bit(src.mute, 4) | bit(src.track, 6);
+        let src2 = src.interpolation_group_delay as u8
+            | bit(src.direct_down_sampling, 2)
+            | (src.deemphasis as u8) << 3
+            | bit(src.auto_deemphasis, 5);
+        let src3 = (src.output_word_length as u8) << 6;
+
+        const fn port_control(port: &PortConfig) -> [u8; 2] {
+            [
+                port.audio_format as u8
+                    | bit(port.is_master, 3)
+                    | (port.output_data_source as u8) << 4
+                    | bit(port.mute, 6),
+                port.clock_divider as u8 | (port.clock_source as u8) << 2,
+            ]
+        }
+        let port_a = port_control(&self.port_a);
+        let port_b = port_control(&self.port_b);
+
+        let transmitter = &self.transmitter;
+        let transmitter1 = bit(transmitter.block_start_input, 0)
+            | bit(transmitter.validity, 1)
+            | bit(transmitter.block_start_mode, 2)
+            | (transmitter.input as u8) << 3
+            | (transmitter.clock_source as u8) << 5
+            | (transmitter.clock_divider as u8) << 6;
+        let transmitter2 = bit(transmitter.line_driver_off, 0)
+            | bit(transmitter.mute, 1)
+            | bit(transmitter.aes_output_off, 2)
+            | bit(transmitter.buffer_transfer_disabled, 3)
+            | bit(transmitter.line_driver_bypass, 4)
+            | bit(transmitter.aes_output_bypass, 5)
+            | (transmitter.bypass_input as u8) << 6;
+
+        [
+            reset,
+            receiver1,
+            receiver2,
+            pll1,
+            pll2,
+            pll3,
+            src1,
+            src2,
+            src3,
+            src.attenuation_left,
+            src.attenuation_right,
+            port_a[0],
+            port_a[1],
+            port_b[0],
+            port_b[1],
+            transmitter1,
+            transmitter2,
+            self.gpo[0] as u8,
+            self.gpo[1] as u8,
+            self.gpo[2] as u8,
+            self.gpo[3] as u8,
+        ]
+    }
+
+    /// Decodes register values, in the order of [`DeviceConfig::REGISTERS`], into a config.
+    ///
+    /// [`SrcConfig::nominal_rates`] can't be read from the device and is always `None`. Reserved
+    /// codes are reported as a [`DecodeError`].
+    pub fn from_image(image: &[u8; 21]) -> Result<DeviceConfig, DecodeError> {
+        fn unpack<R: PackedStruct<ByteArray = [u8; 1]>>(
+            image: &[u8; 21],
+            register: Registers,
+        ) -> Result<R, DecodeError> {
+            let index = DeviceConfig::REGISTERS
+                .iter()
+                .position(|r| *r == register)
+                .unwrap();
+            let value = image[index];
+            R::unpack(&[value]).map_err(|_| DecodeError { register, value })
+        }
+
+        let reset: Reset = unpack(image, Registers::Reset)?;
+        let receiver1: ReceiverControl1 = unpack(image, Registers::ReceiverControl)?;
+        let receiver2: ReceiverControl2 = unpack(image, Registers::ReceiverControl2)?;
+        let pll = ReceiverPllConfiguration::unpack(&[image[3], image[4], image[5]]).map_err(|_| {
+            DecodeError {
+                register: Registers::ReceiverPllConfiguration,
+                value: image[3],
+            }
+        })?;
+        let src1: SrcControl1 = unpack(image, Registers::SrcControl)?;
+        let src2: SrcControl2 = unpack(image, Registers::SrcControl2)?;
+        let src3: SrcControl3 = unpack(image, Registers::SrcControl3)?;
+        let src4: SrcControl4 = unpack(image, Registers::SrcControl4)?;
+        let src5: SrcControl5 = unpack(image, Registers::SrcControl5)?;
+        let port_a1: PortAControl1Register = unpack(image, Registers::PortAControl)?;
+        let port_a2: PortAControl2Register = unpack(image, Registers::PortAControl2)?;
+        let port_b1: PortBControl1Register = unpack(image, Registers::PortBControl)?;
+        let port_b2: PortBControl2Register = unpack(image, Registers::PortBControl2)?;
+        let transmitter1: TransmitterControl1 = unpack(image, Registers::TransmitterControl)?;
+        let transmitter2: TransmitterControl2 = unpack(image, Registers::TransmitterControl2)?;
+        let mut gpo = [GpoFunction::default(); 4];
+        for (function, output) in gpo.iter_mut().zip(Gpo::ALL) {
+            let register: GpoRegister = unpack(image, output.register())?;
+            *function = register.function;
+        }
+
+        let config = DeviceConfig {
+            port_a: PortConfig {
+                audio_format: port_a1.afmt,
+                output_data_source: port_a1.aout,
+                clock_divider: port_a2.adiv,
+                clock_source: port_a2.aclk,
+                is_master: port_a1.am_slave,
+                mute: port_a1.amute,
+            },
+            port_b: PortConfig {
+                audio_format: port_b1.afmt,
+                output_data_source: port_b1.aout,
+                clock_divider: port_b2.adiv,
+                clock_source: port_b2.aclk,
+                is_master: port_b1.am_slave,
+                mute: port_b1.amute,
+            },
+            src: SrcConfig {
+                source: src1.source,
+                clock_source: src1.clock_source,
+                interpolation_group_delay: src2.interpolation_group_delay,
+                deemphasis: src2.deemphasis,
+                auto_deemphasis: src2.autodem,
+                direct_down_sampling: src2.dd_n,
+                track: src1.track,
+                mute: src1.mute,
+                output_word_length: src3.output_word_length,
+                attenuation_left: src4.attenuation,
+                attenuation_right: src5.attenuation,
+                nominal_rates: None,
+            },
+            receiver: ReceiverConfig {
+                input: receiver1.rxmux,
+                reference_clock: receiver1.rxclk,
+                recovered_clock_output: receiver1.rxckoe,
+                recovered_clock_divider: receiver1.rxckod,
+                auto_mute_on_loss_of_lock: receiver2.rxamll,
+                zero_on_loss_of_lock: receiver2.lol,
+                buffer_transfer_disabled: receiver1.rxbtd,
+                pll: PllConfig {
+                    p: pll.p.into(),
+                    j: pll.j.into(),
+                    d: pll.d.into(),
+                },
+            },
+            transmitter: TransmitterConfig {
+                input: transmitter1.txis,
+                clock_source: transmitter1.txclk,
+                clock_divider: transmitter1.txdiv,
+                validity: transmitter1.valid,
+                mute: transmitter2.txmute,
+                line_driver_off: transmitter2.txoff,
+                aes_output_off: transmitter2.aesoff,
+                line_driver_bypass: transmitter2.ldmux,
+                aes_output_bypass: transmitter2.aesmux,
+                bypass_input: transmitter2.bypmux,
+                block_start_input: transmitter1.bssl,
+                block_start_mode: transmitter1.blsm,
+                buffer_transfer_disabled: transmitter2.txbtd,
+            },
+            gpo,
+            power: PowerConfig {
+                port_a: reset.n_pdall && reset.n_pdpa,
+                port_b: reset.n_pdall && reset.n_pdpb,
+                transmitter: reset.n_pdall && reset.n_pdtx,
+                receiver: reset.n_pdall && reset.n_pdrx,
+                src: reset.n_pdall && reset.n_pdsrc,
+            },
+        };
+        match config.reserved_setting() {
+            Some(register) => {
+                let index = DeviceConfig::REGISTERS
+                    .iter()
+                    .position(|r| *r == register)
+                    .unwrap();
+                Err(DecodeError {
+                    register,
+                    value: image[index],
+                })
+            }
+            None => Ok(config),
+        }
+    }
+}
+
+/// Builds a [`DeviceConfig`], starting from the reset state.
+///
+/// Every method is a `const fn`, so a config can be built and checked at compile time:
+///
+/// ```
+/// use src4392::config::{DeviceConfig, PortConfig, PowerConfig};
+/// use src4392::{AudioFormat, Port};
+///
+/// const CONFIG: DeviceConfig = match DeviceConfig::builder()
+///     .power(PowerConfig::ALL_ON)
+///     .port(
+///         Port::A,
+///         PortConfig {
+///             audio_format: AudioFormat::I2S,
+///             ..PortConfig::RESET
+///         },
+///     )
+///     .build()
+/// {
+///     Ok(config) => config,
+///     Err(_) => panic!("invalid config"),
+/// };
+/// const IMAGE: [u8; 21] = CONFIG.to_image();
+/// # assert_eq!(IMAGE[11], 0x01);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DeviceConfigBuilder {
+    config: DeviceConfig,
+}
+
+impl DeviceConfigBuilder {
+    pub const fn new() -> Self {
+        DeviceConfigBuilder {
+            config: DeviceConfig::RESET,
+        }
+    }
+
+    pub const fn port(mut self, port: Port, config: PortConfig) -> Self {
+        match port {
+            Port::A => self.config.port_a = config,
+            Port::B => self.config.port_b = config,
+        }
+        self
+    }
+
+    pub const fn src(mut self, src: SrcConfig) -> Self {
+        self.config.src = src;
+        self
+    }
+
+    pub const fn receiver(mut self, receiver: ReceiverConfig) -> Self {
+        self.config.receiver = receiver;
+        self
+    }
+
+    pub const fn transmitter(mut self, transmitter: TransmitterConfig) -> Self {
+        self.config.transmitter = transmitter;
+        self
+    }
+
+    pub const fn gpo(mut self, gpo: Gpo, function: GpoFunction) -> Self {
+        self.config.gpo[gpo as usize] = function;
+        self
+    }
+
+    pub const fn power(mut self, power: PowerConfig) -> Self {
+        self.config.power = power;
+        self
+    }
+
+    pub const fn build(self) -> Result<DeviceConfig, ConfigError> {
+        match self.config.validate() {
+            Ok(()) => Ok(self.config),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::fixture::{config, device};
+    use crate::sim::SimulatedSrc4392;
+
+    #[test]
+    fn default_config_is_reset_image() {
+        assert_eq!(DeviceConfig::default().to_image(), [0u8; 21]);
+    }
+
+    #[test]
+    fn src_routed_back_to_its_source_port_is_a_loop() {
+        let result = DeviceConfig::builder()
+            .power(PowerConfig::ALL_ON)
+            .src(SrcConfig {
+                source: SrcSource::PortA,
+                ..Default::default()
+            })
+            .port(
+                Port::A,
+                PortConfig {
+                    output_data_source: OutputDataSource::SRC,
+                    ..Default::default()
+                },
+            )
+            .build();
+        assert_eq!(result, Err(ConfigError::RoutingLoop(Port::A)));
+    }
+
+    #[test]
+    fn direct_down_sampling_requires_upsampling() {
+        let builder = DeviceConfig::builder().power(PowerConfig::ALL_ON);
+        let downsampling = SrcConfig {
+            direct_down_sampling: true,
+            nominal_rates: Some((96_000, 48_000)),
+            ..Default::default()
+        };
+        assert_eq!(
+            builder.clone().src(downsampling).build(),
+            Err(ConfigError::DirectDownSamplingWithoutUpsampling)
+        );
+
+        let upsampling = SrcConfig {
+            nominal_rates: Some((44_100, 96_000)),
+            ..downsampling
+        };
+        assert!(builder.src(upsampling).build().is_ok());
+    }
+
+    #[test]
+    fn master_port_on_rxcko_needs_recovered_clock() {
+        let port = PortConfig {
+            clock_source: PortClockSource::Rxcko,
+            is_master: true,
+            ..Default::default()
+        };
+        let builder = DeviceConfig::builder()
+            .power(PowerConfig::ALL_ON)
+            .port(Port::B, port);
+        assert_eq!(
+            builder.clone().build(),
+            Err(ConfigError::MissingPortClock(Port::B))
+        );
+
+        let receiver = ReceiverConfig {
+            recovered_clock_output: true,
+            ..Default::default()
+        };
+        assert!(builder.receiver(receiver).build().is_ok());
+    }
+
+    #[test]
+    fn rxcki_is_checked_against_the_clock_tree() {
+        let config = DeviceConfig::builder()
+            .power(PowerConfig::ALL_ON)
+            .port(
+                Port::A,
+                PortConfig {
+                    clock_source: PortClockSource::Rxcki,
+                    is_master: true,
+                    ..Default::default()
+                },
+            )
+            .build()
+            .unwrap();
+        let mclk_only = ClockTree::new(24_576_000);
+        assert_eq!(
+            config.validate_clocks(&mclk_only),
+            Err(ConfigError::MissingPortClock(Port::A))
+        );
+        assert_eq!(
+            config.validate_clocks(&mclk_only.with_rxcki(22_579_200)),
+            Ok(())
+        );
+
+        let src = DeviceConfig {
+            src: SrcConfig {
+                clock_source: SrcClockSource::Rxcki,
+                ..Default::default()
+            },
+            ..DeviceConfig::RESET
+        };
+        assert_eq!(
+            src.validate_clocks(&mclk_only),
+            Err(ConfigError::MissingSrcClock)
+        );
+
+        let transmitter = DeviceConfig {
+            transmitter: TransmitterConfig {
+                clock_source: TransmitterClockSource::Rxcki,
+                ..Default::default()
+            },
+            power: PowerConfig::ALL_ON,
+            ..DeviceConfig::RESET
+        };
+        assert_eq!(
+            transmitter.validate_clocks(&mclk_only),
+            Err(ConfigError::MissingTransmitterClock)
+        );
+    }
+
+    #[test]
+    fn image_round_trips() {
+        let config = DeviceConfig::builder()
+            .power(PowerConfig {
+                port_b: false,
+                ..PowerConfig::ALL_ON
+            })
+            .port(
+                Port::A,
+                PortConfig {
+                    audio_format: AudioFormat::RightJustified20bit,
+                    output_data_source: OutputDataSource::SRC,
+                    clock_divider: PortMasterClockDivider::_384,
+                    clock_source: PortClockSource::Rxcko,
+                    is_master: true,
+                    mute: false,
+                },
+            )
+            .src(SrcConfig {
+                source: SrcSource::DIR,
+                deemphasis: Deemphasis::_44100Hz,
+                interpolation_group_delay: InterpolationFilterGroupDelay::_16,
+                output_word_length: SrcOutputWordLength::_18bits,
+                track: true,
+                attenuation_left: 3,
+                attenuation_right: 250,
+                ..Default::default()
+            })
+            .receiver(ReceiverConfig {
+                input: ReceiverInput::Rx3,
+                recovered_clock_output: true,
+                recovered_clock_divider: RecoveredClockDivider::_4,
+                buffer_transfer_disabled: true,
+                pll: PllConfig { p: 2, j: 8, d: 1234 },
+                ..Default::default()
+            })
+            .transmitter(TransmitterConfig {
+                input: TransmitterInputSource::SRC,
+                clock_divider: TransmitterClockDivider::_256,
+                bypass_input: BypassMultiplexer::Rx2,
+                block_start_input: true,
+                buffer_transfer_disabled: true,
+                ..Default::default()
+            })
+            .gpo(Gpo::Gpo3, GpoFunction::ReceiverUnlock)
+            .build()
+            .unwrap();
+
+        assert_eq!(DeviceConfig::from_image(&config.to_image()), Ok(config));
+    }
+
+    #[test]
+    fn image_keeps_block_start_and_buffer_bits() {
+        let mut image = DeviceConfig::RESET.to_image();
+        let index = |register| {
+            DeviceConfig::REGISTERS
+                .iter()
+                .position(|r| *r == register)
+                .unwrap()
+        };
+        image[index(Registers::TransmitterControl)] = 0b0000_0101;
+        image[index(Registers::TransmitterControl2)] = 0b0000_1000;
+        image[index(Registers::ReceiverControl)] = 0b0100_0000;
+        assert_eq!(DeviceConfig::from_image(&image).unwrap().to_image(), image);
+    }
+
+    #[test]
+    fn image_packs_port_fields() {
+        let config = DeviceConfig {
+            port_a: PortConfig {
+                audio_format: AudioFormat::I2S,
+                output_data_source: OutputDataSource::SRC,
+                is_master: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let index = DeviceConfig::REGISTERS
+            .iter()
+            .position(|r| *r == Registers::PortAControl)
+            .unwrap();
+        assert_eq!(config.to_image()[index], 0b0011_1001);
+    }
+
+    #[test]
+    fn reserved_codes_are_rejected() {
+        let config = DeviceConfig {
+            port_b: PortConfig {
+                clock_source: PortClockSource::Reserved,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::ReservedSetting(Registers::PortBControl2))
+        );
+        assert_eq!(
+            DeviceConfig::from_image(&config.to_image()),
+            Err(DecodeError {
+                register: Registers::PortBControl2,
+                value: 0b0000_1100,
+            })
+        );
+    }
+
+    #[test]
+    fn load_image_matches_apply() {
+        const IMAGE: [u8; 21] = match DeviceConfig::builder()
+            .power(PowerConfig::ALL_ON)
+            .src(SrcConfig {
+                source: SrcSource::DIR,
+                ..SrcConfig::RESET
+            })
+            .build()
+        {
+            Ok(config) => config.to_image(),
+            Err(_) => panic!(),
+        };
+
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        src4392.set_verify_writes(true);
+        src4392.load_image(&mut spi, &config().to_image()).unwrap();
+        assert_eq!(src4392.read_config(&mut spi).unwrap(), config());
+
+        let applied = SimulatedSrc4392::new();
+        let mut applied_spi = &applied;
+        device(&applied).apply(&mut applied_spi, &config()).unwrap();
+        for register in DeviceConfig::REGISTERS {
+            assert_eq!(
+                sim.register(register),
+                applied.register(register),
+                "{:?}",
+                register
+            );
+        }
+
+        src4392.load_image(&mut spi, &IMAGE).unwrap();
+        assert_eq!(
+            src4392.read_config(&mut spi).unwrap().src.source,
+            SrcSource::DIR
+        );
+    }
+
+    #[test]
+    fn apply_then_read_config() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        src4392.apply(&mut spi, &config()).unwrap();
+        assert_eq!(sim.register(Registers::PortAControl), 0b0011_1001);
+        assert_eq!(src4392.read_config(&mut spi).unwrap(), config());
+    }
+}
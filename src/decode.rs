@@ -0,0 +1,489 @@
+//! Decoding of single register bytes from any source, such as field dumps or a logic analyser
+//! capture.
+//!
+//! Every enum field has a variant for each code, the ones the datasheet reserves included, so a
+//! register type unpacks from any bit pattern. [`decode_any`] never panics.
+//!
+//! [`Decoded`] implements [`fmt::Display`] using the datasheet field names, so it can be written
+//! through any [`fmt::Write`], an RTT channel as well as a `String`:
+//!
+//! ```text
+//! PortAControl(0x03)=0x19: AFMT=I2S, AM_S=master, AOUT=SRC, AMUTE=0
+//! ```
+
+use core::fmt::{self, Debug};
+
+use packed_struct::prelude::*;
+
+use crate::gpo::GpoRegister;
+use crate::interrupt::{
+    GlobalInterruptStatus, SrcAndDitInterruptMask, SrcAndDitInterruptMode, SrcAndDitStatus,
+};
+use crate::port_control::{
+    PortAControl1Register, PortAControl2Register, PortBControl1Register, PortBControl2Register,
+};
+use crate::receiver::{
+    NonPcmAudioDetection, ReceiverControl1, ReceiverControl2, ReceiverInterruptMask1,
+    ReceiverInterruptMask2, ReceiverInterruptMode1, ReceiverInterruptMode2, ReceiverInterruptMode3,
+    ReceiverStatus1, ReceiverStatus2,
+};
+use crate::registers::Registers;
+use crate::reset::Reset;
+use crate::sample_rate_converter::{
+    SrcControl1, SrcControl2, SrcControl3, SrcControl4, SrcControl5,
+};
+use crate::transmitter::{TransmitterControl1, TransmitterControl2, TransmitterControl3};
+
+/// The fields of a register byte.
+#[derive(Debug)]
+pub enum Fields {
+    Reset(Reset),
+    GlobalInterruptStatus(GlobalInterruptStatus),
+    PortAControl1(PortAControl1Register),
+    PortAControl2(PortAControl2Register),
+    PortBControl1(PortBControl1Register),
+    PortBControl2(PortBControl2Register),
+    TransmitterControl1(TransmitterControl1),
+    TransmitterControl2(TransmitterControl2),
+    TransmitterControl3(TransmitterControl3),
+    SrcAndDitStatus(SrcAndDitStatus),
+    SrcAndDitInterruptMask(SrcAndDitInterruptMask),
+    SrcAndDitInterruptMode(SrcAndDitInterruptMode),
+    ReceiverControl1(ReceiverControl1),
+    ReceiverControl2(ReceiverControl2),
+    NonPcmAudioDetection(NonPcmAudioDetection),
+    ReceiverStatus1(ReceiverStatus1),
+    ReceiverStatus2(ReceiverStatus2),
+    ReceiverInterruptMask1(ReceiverInterruptMask1),
+    ReceiverInterruptMask2(ReceiverInterruptMask2),
+    ReceiverInterruptMode1(ReceiverInterruptMode1),
+    ReceiverInterruptMode2(ReceiverInterruptMode2),
+    ReceiverInterruptMode3(ReceiverInterruptMode3),
+    Gpo(GpoRegister),
+    SrcControl1(SrcControl1),
+    SrcControl2(SrcControl2),
+    SrcControl3(SrcControl3),
+    SrcControl4(SrcControl4),
+    SrcControl5(SrcControl5),
+    /// The address has no single byte register type: part of a multi byte register such as the
+    /// PLL configuration or the SRC ratio, a data register such as the Q-channel, or no register
+    /// at all. Only [`Decoded::value`] is available.
+    Raw,
+}
+
+/// A register byte and its fields. The raw value is kept, so bits a register type drops, such as
+/// reserved bits, aren't lost.
+#[derive(Debug)]
+pub struct Decoded {
+    pub address: u8,
+    /// The page 0 register at `address`, if there is one.
+    pub register: Option<Registers>,
+    pub value: u8,
+    pub fields: Fields,
+}
+
+/// Decodes `value` as the page 0 register at `address`.
+pub fn decode_any(address: u8, value: u8) -> Decoded {
+    fn unpack<R: PackedStruct<ByteArray = [u8; 1]>>(value: u8, fields: fn(R) -> Fields) -> Fields {
+        R::unpack(&[value]).map_or(Fields::Raw, fields)
+    }
+
+    let register = Registers::from_address(address);
+    let fields = match register {
+        Some(Registers::Reset) => unpack(value, Fields::Reset),
+        Some(Registers::GlobalInterruptStatus) => unpack(value, Fields::GlobalInterruptStatus),
+        Some(Registers::PortAControl) => unpack(value, Fields::PortAControl1),
+        Some(Registers::PortAControl2) => unpack(value, Fields::PortAControl2),
+        Some(Registers::PortBControl) => unpack(value, Fields::PortBControl1),
+        Some(Registers::PortBControl2) => unpack(value, Fields::PortBControl2),
+        Some(Registers::TransmitterControl) => unpack(value, Fields::TransmitterControl1),
+        Some(Registers::TransmitterControl2) => unpack(value, Fields::TransmitterControl2),
+        Some(Registers::TransmitterControl3) => unpack(value, Fields::TransmitterControl3),
+        Some(Registers::SrcAndDitStatus) => unpack(value, Fields::SrcAndDitStatus),
+        Some(Registers::SrcAndDitInterruptMask) => unpack(value, Fields::SrcAndDitInterruptMask),
+        Some(Registers::SrcAndDitInterruptMode) => unpack(value, Fields::SrcAndDitInterruptMode),
+        Some(Registers::ReceiverControl) => unpack(value, Fields::ReceiverControl1),
+        Some(Registers::ReceiverControl2) => unpack(value, Fields::ReceiverControl2),
+        Some(Registers::NonPcmAudioDetection) => unpack(value, Fields::NonPcmAudioDetection),
+        Some(Registers::ReceiverStatus) => unpack(value, Fields::ReceiverStatus1),
+        Some(Registers::ReceiverStatus2) => unpack(value, Fields::ReceiverStatus2),
+        Some(Registers::ReceiverInterruptMask) => unpack(value, Fields::ReceiverInterruptMask1),
+        Some(Registers::ReceiverInterruptMask2) => unpack(value, Fields::ReceiverInterruptMask2),
+        Some(Registers::ReceiverInterruptMode) => unpack(value, Fields::ReceiverInterruptMode1),
+        Some(Registers::ReceiverInterruptMode2) => unpack(value, Fields::ReceiverInterruptMode2),
+        Some(Registers::ReceiverInterruptMode3) => unpack(value, Fields::ReceiverInterruptMode3),
+        Some(Registers::Gpo1 | Registers::Gpo2 | Registers::Gpo3 | Registers::Gpo4) => {
+            unpack(value, Fields::Gpo)
+        }
+        Some(Registers::SrcControl) => unpack(value, Fields::SrcControl1),
+        Some(Registers::SrcControl2) => unpack(value, Fields::SrcControl2),
+        Some(Registers::SrcControl3) => unpack(value, Fields::SrcControl3),
+        Some(Registers::SrcControl4) => unpack(value, Fields::SrcControl4),
+        Some(Registers::SrcControl5) => unpack(value, Fields::SrcControl5),
+        _ => Fields::Raw,
+    };
+    Decoded {
+        address,
+        register,
+        value,
+        fields,
+    }
+}
+
+/// A field value as shown by [`Decoded`]'s [`fmt::Display`].
+enum Value<'a> {
+    Bit(bool),
+    Number(u32),
+    /// An enum variant, without the leading underscore of names such as `_256`.
+    Enum(&'a dyn Debug),
+    Text(&'a str),
+    /// Attenuation in 0.5dB steps.
+    Attenuation(u8),
+}
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bit(bit) => write!(f, "{}", *bit as u8),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Enum(value) => {
+                use fmt::Write;
+                write!(TrimUnderscore { f, first: true }, "{:?}", value)
+            }
+            Value::Text(text) => f.write_str(text),
+            Value::Attenuation(steps) => write!(f, "-{}.{}dB", steps / 2, (steps % 2) * 5),
+        }
+    }
+}
+
+struct TrimUnderscore<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    first: bool,
+}
+
+impl fmt::Write for TrimUnderscore<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let s = match self.first {
+            true => s.strip_prefix('_').unwrap_or(s),
+            false => s,
+        };
+        self.first = false;
+        self.f.write_str(s)
+    }
+}
+
+fn write_fields(f: &mut fmt::Formatter<'_>, fields: &[(&str, Value<'_>)]) -> fmt::Result {
+    for (index, (name, value)) in fields.iter().enumerate() {
+        let separator = if index == 0 { ": " } else { ", " };
+        write!(f, "{}{}={}", separator, name, value)?;
+    }
+    Ok(())
+}
+
+fn master(is_master: bool) -> Value<'static> {
+    Value::Text(if is_master { "master" } else { "slave" })
+}
+
+impl Fields {
+    fn write(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        register: Option<Registers>,
+        value: u8,
+    ) -> fmt::Result {
+        use Value::{Attenuation, Bit, Enum, Number};
+        match self {
+            Fields::Reset(r) => write_fields(
+                f,
+                &[
+                    ("PDSRC", Bit(r.n_pdsrc)),
+                    ("PDRX", Bit(r.n_pdrx)),
+                    ("PDTX", Bit(r.n_pdtx)),
+                    ("PDPB", Bit(r.n_pdpb)),
+                    ("PDPA", Bit(r.n_pdpa)),
+                    ("PDALL", Bit(r.n_pdall)),
+                    ("RESET", Bit(r.reset)),
+                ],
+            ),
+            Fields::GlobalInterruptStatus(r) => write_fields(
+                f,
+                &[("SRC", Bit(r.src)), ("RX", Bit(r.rx)), ("TX", Bit(r.tx))],
+            ),
+            Fields::PortAControl1(r) => write_fields(
+                f,
+                &[
+                    ("AFMT", Enum(&r.afmt)),
+                    ("AM_S", master(r.am_slave)),
+                    ("AOUT", Enum(&r.aout)),
+                    ("AMUTE", Bit(r.amute)),
+                ],
+            ),
+            Fields::PortAControl2(r) => {
+                write_fields(f, &[("ADIV", Enum(&r.adiv)), ("ACLK", Enum(&r.aclk))])
+            }
+            Fields::PortBControl1(r) => write_fields(
+                f,
+                &[
+                    ("BFMT", Enum(&r.afmt)),
+                    ("BM_S", master(r.am_slave)),
+                    ("BOUT", Enum(&r.aout)),
+                    ("BMUTE", Bit(r.amute)),
+                ],
+            ),
+            Fields::PortBControl2(r) => {
+                write_fields(f, &[("BDIV", Enum(&r.adiv)), ("BCLK", Enum(&r.aclk))])
+            }
+            Fields::TransmitterControl1(r) => write_fields(
+                f,
+                &[
+                    ("BSSL", Bit(r.bssl)),
+                    ("VALID", Bit(r.valid)),
+                    ("BLSM", Bit(r.blsm)),
+                    ("TXIS", Enum(&r.txis)),
+                    ("TXCLK", Enum(&r.txclk)),
+                    ("TXDIV", Enum(&r.txdiv)),
+                ],
+            ),
+            Fields::TransmitterControl2(r) => write_fields(
+                f,
+                &[
+                    ("TXOFF", Bit(r.txoff)),
+                    ("TXMUTE", Bit(r.txmute)),
+                    ("AESOFF", Bit(r.aesoff)),
+                    ("TXBTD", Bit(r.txbtd)),
+                    ("LDMUX", Bit(r.ldmux)),
+                    ("AESMUX", Bit(r.aesmux)),
+                    ("BYPMUX", Enum(&r.bypmux)),
+                ],
+            ),
+            Fields::TransmitterControl3(r) => write_fields(
+                f,
+                &[
+                    ("TXCUS", Number((*r.txcus).into())),
+                    ("VALSEL", Bit(r.valsel)),
+                ],
+            ),
+            Fields::SrcAndDitStatus(r) => write_fields(
+                f,
+                &[
+                    ("TBTI", Bit(r.tbti)),
+                    ("TSLIP", Bit(r.tslip)),
+                    ("READY", Bit(r.ready)),
+                    ("RATIO", Bit(r.ratio)),
+                ],
+            ),
+            Fields::SrcAndDitInterruptMask(r) => write_fields(
+                f,
+                &[
+                    ("MTBTI", Bit(r.mtbti)),
+                    ("MTSLIP", Bit(r.mtslip)),
+                    ("MREADY", Bit(r.mready)),
+                    ("MRATIO", Bit(r.mratio)),
+                ],
+            ),
+            Fields::SrcAndDitInterruptMode(r) => write_fields(
+                f,
+                &[
+                    ("TBTIM", Enum(&r.tbtim)),
+                    ("TSLIPM", Enum(&r.tslipm)),
+                    ("READYM", Enum(&r.readym)),
+                    ("RATIOM", Enum(&r.ratiom)),
+                ],
+            ),
+            Fields::ReceiverControl1(r) => write_fields(
+                f,
+                &[
+                    ("RXMUX", Enum(&r.rxmux)),
+                    ("RXCKOE", Bit(r.rxckoe)),
+                    ("RXCKOD", Enum(&r.rxckod)),
+                    ("RXCLK", Enum(&r.rxclk)),
+                    ("RXBTD", Bit(r.rxbtd)),
+                ],
+            ),
+            Fields::ReceiverControl2(r) => {
+                write_fields(f, &[("RXAMLL", Bit(r.rxamll)), ("LOL", Bit(r.lol))])
+            }
+            Fields::NonPcmAudioDetection(r) => write_fields(
+                f,
+                &[("IEC61937", Bit(r.iec61937)), ("DTS_CD", Bit(r.dts_cd))],
+            ),
+            Fields::ReceiverStatus1(r) => write_fields(
+                f,
+                &[
+                    ("RBTI", Bit(r.rbti)),
+                    ("QCHG", Bit(r.qchg)),
+                    ("QCRC", Bit(r.qcrc)),
+                    ("CSCRC", Bit(r.cscrc)),
+                    ("PARITY", Bit(r.parity)),
+                    ("VBIT", Bit(r.vbit)),
+                    ("BPERR", Bit(r.bperr)),
+                    ("UNLOCK", Bit(r.unlock)),
+                ],
+            ),
+            Fields::ReceiverStatus2(r) => {
+                write_fields(f, &[("OSLIP", Bit(r.oslip)), ("CSCHG", Bit(r.cschg))])
+            }
+            Fields::ReceiverInterruptMask1(r) => write_fields(
+                f,
+                &[
+                    ("MRBTI", Bit(r.mrbti)),
+                    ("MQCHG", Bit(r.mqchg)),
+                    ("MQCRC", Bit(r.mqcrc)),
+                    ("MCSCRC", Bit(r.mcscrc)),
+                    ("MPARITY", Bit(r.mparity)),
+                    ("MVBIT", Bit(r.mvbit)),
+                    ("MBPERR", Bit(r.mbperr)),
+                    ("MUNLOCK", Bit(r.munlock)),
+                ],
+            ),
+            Fields::ReceiverInterruptMask2(r) => {
+                write_fields(f, &[("MOSLIP", Bit(r.moslip)), ("MCSCHG", Bit(r.mcschg))])
+            }
+            Fields::ReceiverInterruptMode1(r) => write_fields(
+                f,
+                &[
+                    ("RBTIM", Enum(&r.rbtim)),
+                    ("QCHGM", Enum(&r.qchgm)),
+                    ("QCRCM", Enum(&r.qcrcm)),
+                    ("CSCRCM", Enum(&r.cscrcm)),
+                ],
+            ),
+            Fields::ReceiverInterruptMode2(r) => write_fields(
+                f,
+                &[
+                    ("PARITYM", Enum(&r.paritym)),
+                    ("VBITM", Enum(&r.vbitm)),
+                    ("BPERRM", Enum(&r.bperrm)),
+                    ("UNLOCKM", Enum(&r.unlockm)),
+                ],
+            ),
+            Fields::ReceiverInterruptMode3(r) => write_fields(
+                f,
+                &[("OSLIPM", Enum(&r.oslipm)), ("CSCHGM", Enum(&r.cschgm))],
+            ),
+            Fields::Gpo(r) => write_fields(f, &[("GPO", Enum(&r.function))]),
+            Fields::SrcControl1(r) => write_fields(
+                f,
+                &[
+                    ("SRCIS", Enum(&r.source)),
+                    ("SRCCLK", Enum(&r.clock_source)),
+                    ("MUTE", Bit(r.mute)),
+                    ("TRACK", Bit(r.track)),
+                ],
+            ),
+            Fields::SrcControl2(r) => write_fields(
+                f,
+                &[
+                    ("IGRP", Enum(&r.interpolation_group_delay)),
+                    ("DDN", Bit(r.dd_n)),
+                    ("DEM", Enum(&r.deemphasis)),
+                    ("AUTODEM", Bit(r.autodem)),
+                ],
+            ),
+            Fields::SrcControl3(r) => write_fields(f, &[("OWL", Enum(&r.output_word_length))]),
+            Fields::SrcControl4(r) => write_fields(f, &[("AL", Attenuation(r.attenuation))]),
+            Fields::SrcControl5(r) => write_fields(f, &[("AR", Attenuation(r.attenuation))]),
+            Fields::Raw => {
+                let value = u32::from(value);
+                match register {
+                    Some(Registers::ReceiverPllConfiguration) => write_fields(
+                        f,
+                        &[("P", Number(value >> 4)), ("J[5:2]", Number(value & 0x0F))],
+                    ),
+                    Some(Registers::ReceiverPllConfiguration2) => write_fields(
+                        f,
+                        &[
+                            ("J[1:0]", Number(value >> 6)),
+                            ("D[13:8]", Number(value & 0x3F)),
+                        ],
+                    ),
+                    Some(Registers::ReceiverPllConfiguration3) => {
+                        write_fields(f, &[("D[7:0]", Number(value))])
+                    }
+                    Some(Registers::SrcInputOutputRatio1) => write_fields(
+                        f,
+                        &[
+                            ("SRI", Number(value >> 3)),
+                            ("SRF[10:8]", Number(value & 0x07)),
+                        ],
+                    ),
+                    Some(Registers::SrcInputOutputRatio2) => {
+                        write_fields(f, &[("SRF[7:0]", Number(value))])
+                    }
+                    Some(Registers::PageSelection) => {
+                        write_fields(f, &[("PAGE", Number(value & 0x03))])
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Writes `Name(0xaa)=0xvv: FIELD=value, ...`, or `0xaa=0xvv` for an address with no register.
+impl fmt::Display for Decoded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.register {
+            Some(register) => write!(
+                f,
+                "{:?}(0x{:02X})=0x{:02X}",
+                register, self.address, self.value
+            )?,
+            None => write!(f, "0x{:02X}=0x{:02X}", self.address, self.value)?,
+        }
+        self.fields.write(f, self.register, self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port_control::{AudioFormat, PortClockSource};
+
+    #[test]
+    fn reserved_codes_decode() {
+        let decoded = decode_any(Registers::PortAControl as u8, 0b1000_0010);
+        assert_eq!(decoded.register, Some(Registers::PortAControl));
+        assert_eq!(decoded.value, 0b1000_0010);
+        match decoded.fields {
+            Fields::PortAControl1(reg) => assert_eq!(reg.afmt, AudioFormat::Reserved010),
+            fields => panic!("{:?}", fields),
+        }
+
+        match decode_any(Registers::PortBControl2 as u8, 0b0000_1100).fields {
+            Fields::PortBControl2(reg) => assert_eq!(reg.aclk, PortClockSource::Reserved),
+            fields => panic!("{:?}", fields),
+        }
+
+        let decoded = decode_any(0x40, 0xFF);
+        assert_eq!(decoded.register, None);
+        assert!(matches!(decoded.fields, Fields::Raw));
+    }
+
+    #[test]
+    fn display() {
+        use std::string::ToString;
+
+        assert_eq!(
+            decode_any(0x03, 0x19).to_string(),
+            "PortAControl(0x03)=0x19: AFMT=I2S, AM_S=master, AOUT=OtherPort, AMUTE=0"
+        );
+        assert_eq!(
+            decode_any(0x04, 0x01).to_string(),
+            "PortAControl2(0x04)=0x01: ADIV=256, ACLK=Mclk"
+        );
+        assert_eq!(
+            decode_any(0x30, 0x11).to_string(),
+            "SrcControl4(0x30)=0x11: AL=-8.5dB"
+        );
+        assert_eq!(
+            decode_any(0x32, 0x0A).to_string(),
+            "SrcInputOutputRatio1(0x32)=0x0A: SRI=1, SRF[10:8]=2"
+        );
+        assert_eq!(
+            decode_any(0x1F, 0x42).to_string(),
+            "AudioCdQChannelSubCode1(0x1F)=0x42"
+        );
+        assert_eq!(decode_any(0x40, 0x00).to_string(), "0x40=0x00");
+    }
+}
@@ -0,0 +1,181 @@
+//! A stable binary layout for raw register dumps, so dumps taken in the field can be loaded into
+//! desktop tools.
+//!
+//! Layout version 1:
+//!
+//! | offset | length | contents                                                          |
+//! |--------|--------|-------------------------------------------------------------------|
+//! | 0      | 4      | magic, `S392`                                                     |
+//! | 4      | 1      | layout version                                                    |
+//! | 5      | 1      | flags, bit 0 set when the buffer pages are present                |
+//! | 6      | 2      | reserved, zero                                                    |
+//! | 8      | 52     | page 0 indexed by register address, address 0x00 is always zero   |
+//! | 60     | 96     | page 1, the DIR channel status and user data buffers (optional)   |
+//! | 156    | 96     | page 2, the DIT channel status and user data buffers (optional)   |
+//!
+//! Page 0 includes the status registers as read when the dump was taken. Reading them clears the
+//! interrupt flags latched in them, so a flag shows up in one dump and is gone from the device
+//! afterwards.
+
+use crate::registers::Registers;
+
+pub const MAGIC: [u8; 4] = *b"S392";
+pub const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 8;
+/// Page 0 is stored from address 0x00 up to and including [`Registers::SrcInputOutputRatio2`].
+pub const CONTROL_PAGE_LEN: usize = Registers::SrcInputOutputRatio2 as usize + 1;
+pub const BUFFER_PAGE_LEN: usize = 0x60;
+/// The length of a dump holding only page 0.
+pub const REGISTER_DUMP_LEN: usize = HEADER_LEN + CONTROL_PAGE_LEN;
+/// The length of a dump holding page 0 and both buffer pages.
+pub const FULL_DUMP_LEN: usize = REGISTER_DUMP_LEN + 2 * BUFFER_PAGE_LEN;
+
+const FLAG_BUFFERS: u8 = 0b0000_0001;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DumpError {
+    TooShort,
+    BadMagic,
+    UnsupportedVersion(u8),
+}
+
+/// A parsed view over dump bytes.
+#[derive(Copy, Clone, Debug)]
+pub struct RegisterDump<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> RegisterDump<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, DumpError> {
+        if bytes.len() < REGISTER_DUMP_LEN {
+            return Err(DumpError::TooShort);
+        }
+        if bytes[0..4] != MAGIC {
+            return Err(DumpError::BadMagic);
+        }
+        if bytes[4] != VERSION {
+            return Err(DumpError::UnsupportedVersion(bytes[4]));
+        }
+        if bytes[5] & FLAG_BUFFERS != 0 && bytes.len() < FULL_DUMP_LEN {
+            return Err(DumpError::TooShort);
+        }
+        Ok(Self { bytes })
+    }
+
+    /// The dumped value of a page 0 register. [`Registers::PageSelection`] isn't dumped and reads
+    /// as zero.
+    pub fn register(&self, register: Registers) -> u8 {
+        self.control_page()
+            .get(register as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Page 0, indexed by register address.
+    pub fn control_page(&self) -> &'a [u8] {
+        &self.bytes[HEADER_LEN..REGISTER_DUMP_LEN]
+    }
+
+    pub fn receiver_buffers(&self) -> Option<&'a [u8]> {
+        self.has_buffers()
+            .then(|| &self.bytes[REGISTER_DUMP_LEN..REGISTER_DUMP_LEN + BUFFER_PAGE_LEN])
+    }
+
+    pub fn transmitter_buffers(&self) -> Option<&'a [u8]> {
+        self.has_buffers()
+            .then(|| &self.bytes[REGISTER_DUMP_LEN + BUFFER_PAGE_LEN..FULL_DUMP_LEN])
+    }
+
+    fn has_buffers(&self) -> bool {
+        self.bytes[5] & FLAG_BUFFERS != 0
+    }
+}
+
+/// Writes the header to the first [`HEADER_LEN`] bytes of `out`, for a dump that holds the buffer
+/// pages when `buffers` is set.
+pub fn write_header(out: &mut [u8], buffers: bool) {
+    out[0..4].copy_from_slice(&MAGIC);
+    out[4] = VERSION;
+    out[5] = if buffers { FLAG_BUFFERS } else { 0 };
+    out[6] = 0;
+    out[7] = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::fixture::{config, device};
+    use crate::registers::Page;
+    use crate::sim::SimulatedSrc4392;
+
+    #[test]
+    fn parse_rejects_bad_headers() {
+        let mut bytes = [0u8; REGISTER_DUMP_LEN];
+        assert_eq!(
+            RegisterDump::parse(&bytes[..10]).unwrap_err(),
+            DumpError::TooShort
+        );
+        assert_eq!(RegisterDump::parse(&bytes).unwrap_err(), DumpError::BadMagic);
+
+        write_header(&mut bytes, false);
+        bytes[4] = 7;
+        assert_eq!(
+            RegisterDump::parse(&bytes).unwrap_err(),
+            DumpError::UnsupportedVersion(7)
+        );
+
+        write_header(&mut bytes, true);
+        assert_eq!(RegisterDump::parse(&bytes).unwrap_err(), DumpError::TooShort);
+    }
+
+    #[test]
+    fn registers_are_indexed_by_address() {
+        let mut bytes = [0u8; FULL_DUMP_LEN];
+        write_header(&mut bytes, true);
+        bytes[HEADER_LEN + 0x2E] = 0x42;
+        bytes[REGISTER_DUMP_LEN + BUFFER_PAGE_LEN] = 0x99;
+
+        let dump = RegisterDump::parse(&bytes).unwrap();
+        assert_eq!(dump.register(Registers::SrcControl2), 0x42);
+        assert_eq!(dump.register(Registers::PageSelection), 0);
+        assert_eq!(dump.transmitter_buffers().unwrap()[0], 0x99);
+    }
+
+    #[test]
+    fn dump_and_restore() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        src4392.apply(&mut spi, &config()).unwrap();
+        sim.set_receiver_buffers([0x5A; BUFFER_PAGE_LEN]);
+        let mut dump = [0u8; FULL_DUMP_LEN];
+        assert_eq!(src4392.dump(&mut spi, &mut dump).unwrap(), FULL_DUMP_LEN);
+        assert_eq!(dump[REGISTER_DUMP_LEN], 0x5A);
+
+        let other = SimulatedSrc4392::new();
+        let mut other_spi = &other;
+        let mut other_device = device(&other);
+        other_device.restore(&mut other_spi, &dump).unwrap();
+        assert_eq!(other_device.read_config(&mut other_spi).unwrap(), config());
+        assert_eq!(other.receiver_buffers(), [0; BUFFER_PAGE_LEN]);
+    }
+
+    #[test]
+    fn restore_selects_the_control_page() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        src4392.apply(&mut spi, &config()).unwrap();
+        let mut dump = [0u8; REGISTER_DUMP_LEN];
+        src4392.dump(&mut spi, &mut dump).unwrap();
+
+        let other = SimulatedSrc4392::new();
+        let mut other_spi = &other;
+        let mut other_device = device(&other);
+        other.set_register(Registers::PageSelection, Page::TransmitterBuffers as u8);
+        other_device.restore(&mut other_spi, &dump).unwrap();
+        assert_eq!(other_device.read_config(&mut other_spi).unwrap(), config());
+        assert_eq!(other.transmitter_buffers(), [0; BUFFER_PAGE_LEN]);
+    }
+}
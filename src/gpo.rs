@@ -0,0 +1,60 @@
+use packed_struct::prelude::*;
+
+use crate::registers::Registers;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Gpo {
+    Gpo1,
+    Gpo2,
+    Gpo3,
+    Gpo4,
+}
+
+impl Gpo {
+    pub const ALL: [Gpo; 4] = [Gpo::Gpo1, Gpo::Gpo2, Gpo::Gpo3, Gpo::Gpo4];
+
+    pub fn register(&self) -> Registers {
+        match self {
+            Gpo::Gpo1 => Registers::Gpo1,
+            Gpo::Gpo2 => Registers::Gpo2,
+            Gpo::Gpo3 => Registers::Gpo3,
+            Gpo::Gpo4 => Registers::Gpo4,
+        }
+    }
+}
+
+/// The signal driven onto a general purpose output pin.
+#[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GpoFunction {
+    #[default]
+    Low = 0b0000,
+    High = 0b0001,
+    SrcInterrupt = 0b0010,
+    TransmitterInterrupt = 0b0011,
+    ReceiverInterrupt = 0b0100,
+    ReceiverEmphasis = 0b0101,
+    ReceiverNonAudio = 0b0110,
+    ReceiverNonValid = 0b0111,
+    ReceiverChannelStatus = 0b1000,
+    ReceiverUserData = 0b1001,
+    ReceiverBlockStart = 0b1010,
+    ReceiverCopy = 0b1011,
+    ReceiverOriginal = 0b1100,
+    ReceiverUnlock = 0b1101,
+    TransmitterBlockStart = 0b1110,
+    ReceiverQChannelSync = 0b1111,
+}
+
+/// Layout shared by the four GPO registers. Use [`Gpo::register`] for the address.
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct GpoRegister {
+    #[packed_field(bits = "0..4", ty = "enum")]
+    pub function: GpoFunction,
+    #[packed_field(bits = "4..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZeroes<packed_bits::Bits<4>>,
+}
@@ -1,9 +1,13 @@
 use packed_struct::prelude::*;
 
+use crate::{registers::Registers, RegisterAddress};
+
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
 pub struct GlobalInterruptStatus {
     #[packed_field(bits = "7:3")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _reserved: ReservedZeroes<packed_bits::Bits<5>>,
     #[packed_field(bits = "2")]
     pub tx: bool,
@@ -12,3 +16,109 @@ pub struct GlobalInterruptStatus {
     #[packed_field(bits = "0")]
     pub src: bool,
 }
+impl GlobalInterruptStatus {
+    pub const REGISTER_ADDRESS: Registers = Registers::GlobalInterruptStatus;
+}
+impl RegisterAddress<Registers> for GlobalInterruptStatus {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+/// When an interrupt source sets its status bit and drives the interrupt output.
+#[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterruptMode {
+    /// The status bit is latched when the condition starts. Reading the status register clears it.
+    #[default]
+    RisingEdge = 0b00,
+    /// The status bit is latched when the condition ends. Reading the status register clears it.
+    FallingEdge = 0b01,
+    /// The status bit follows the condition.
+    Level = 0b10,
+    /// Not defined by the datasheet.
+    Reserved = 0b11,
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct SrcAndDitStatus {
+    #[packed_field(bits = "0")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZero<packed_bits::Bits<1>>,
+    /// DIT block start.
+    #[packed_field(bits = "1")]
+    pub tbti: bool,
+    /// DIT source data slipped.
+    #[packed_field(bits = "2")]
+    pub tslip: bool,
+    /// The SRC output is valid.
+    #[packed_field(bits = "3")]
+    pub ready: bool,
+    /// The SRC input to output ratio has been measured.
+    #[packed_field(bits = "4")]
+    pub ratio: bool,
+    #[packed_field(bits = "5..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved2: ReservedZeroes<packed_bits::Bits<3>>,
+}
+impl SrcAndDitStatus {
+    pub const REGISTER_ADDRESS: Registers = Registers::SrcAndDitStatus;
+}
+impl RegisterAddress<Registers> for SrcAndDitStatus {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+/// true enables the interrupt for the matching [`SrcAndDitStatus`] bit.
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct SrcAndDitInterruptMask {
+    #[packed_field(bits = "0")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZero<packed_bits::Bits<1>>,
+    #[packed_field(bits = "1")]
+    pub mtbti: bool,
+    #[packed_field(bits = "2")]
+    pub mtslip: bool,
+    #[packed_field(bits = "3")]
+    pub mready: bool,
+    #[packed_field(bits = "4")]
+    pub mratio: bool,
+    #[packed_field(bits = "5..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved2: ReservedZeroes<packed_bits::Bits<3>>,
+}
+impl SrcAndDitInterruptMask {
+    pub const REGISTER_ADDRESS: Registers = Registers::SrcAndDitInterruptMask;
+}
+impl RegisterAddress<Registers> for SrcAndDitInterruptMask {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct SrcAndDitInterruptMode {
+    #[packed_field(bits = "0..2", ty = "enum")]
+    pub tbtim: InterruptMode,
+    #[packed_field(bits = "2..4", ty = "enum")]
+    pub tslipm: InterruptMode,
+    #[packed_field(bits = "4..6", ty = "enum")]
+    pub readym: InterruptMode,
+    #[packed_field(bits = "6..8", ty = "enum")]
+    pub ratiom: InterruptMode,
+}
+impl SrcAndDitInterruptMode {
+    pub const REGISTER_ADDRESS: Registers = Registers::SrcAndDitInterruptMode;
+}
+impl RegisterAddress<Registers> for SrcAndDitInterruptMode {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
@@ -0,0 +1,173 @@
+//! The delay audio takes through the SRC, for lip-sync compensation.
+//!
+//! The SRC delays audio by the group delays of its two filters: the interpolation filter on the
+//! input side, set by [`InterpolationFilterGroupDelay`] in input samples, and the decimation
+//! filter on the output side, a fixed 36 output samples. Direct down sampling bypasses the
+//! decimation filter.
+//!
+//! ```
+//! use src4392::clock::Rate;
+//! use src4392::config::SrcConfig;
+//! use src4392::latency::src_latency;
+//!
+//! let latency = src_latency(&SrcConfig::RESET, Rate::Hz96000.into(), Rate::Hz48000.into()).unwrap();
+//! assert_eq!(latency.output_samples, 68);
+//! assert_eq!(latency.micros, 1417);
+//! ```
+
+use crate::clock::{ClockError, SampleRate};
+use crate::config::SrcConfig;
+use crate::sample_rate_converter::InterpolationFilterGroupDelay;
+
+/// Group delay of the decimation filter, in output samples.
+pub const DECIMATION_GROUP_DELAY: u32 = 36;
+
+/// The delay through the SRC.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SrcLatency {
+    /// The delay in samples at the output rate, rounded to the nearest sample.
+    pub output_samples: u32,
+    /// The delay in microseconds, rounded to the nearest microsecond.
+    pub micros: u32,
+}
+
+impl InterpolationFilterGroupDelay {
+    /// The group delay, in input samples.
+    pub const fn samples(&self) -> u32 {
+        match self {
+            InterpolationFilterGroupDelay::_64 => 64,
+            InterpolationFilterGroupDelay::_32 => 32,
+            InterpolationFilterGroupDelay::_16 => 16,
+            InterpolationFilterGroupDelay::_8 => 8,
+        }
+    }
+}
+
+/// The delay through the SRC set up with `config`, converting from `input` to `output`.
+///
+/// Only the filter settings of `config` are used. Returns [`ClockError::RateOutOfRange`] when
+/// either rate is zero.
+pub fn src_latency(
+    config: &SrcConfig,
+    input: SampleRate,
+    output: SampleRate,
+) -> Result<SrcLatency, ClockError> {
+    for rate in [input, output] {
+        if rate.hz() == 0 {
+            return Err(ClockError::RateOutOfRange(rate));
+        }
+    }
+    let interpolation = u64::from(config.interpolation_group_delay.samples());
+    let decimation = if config.direct_down_sampling {
+        0
+    } else {
+        u64::from(DECIMATION_GROUP_DELAY)
+    };
+    let (input_hz, output_hz) = (u64::from(input.hz()), u64::from(output.hz()));
+
+    // Both delays over the common denominator input_hz * output_hz, in seconds.
+    let numerator = interpolation * output_hz + decimation * input_hz;
+    let denominator = input_hz * output_hz;
+    Ok(SrcLatency {
+        output_samples: rounded_div(numerator, input_hz) as u32,
+        micros: rounded_div(numerator * 1_000_000, denominator) as u32,
+    })
+}
+
+fn rounded_div(numerator: u64, denominator: u64) -> u64 {
+    (numerator + denominator / 2) / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Rate;
+    use crate::sample_rate_converter::{Deemphasis, SrcClockSource, SrcSource};
+    use crate::sim::fixture::device;
+    use crate::sim::{Event, SimulatedSrc4392};
+    use crate::Error;
+
+    fn latency(config: &SrcConfig, input: Rate, output: Rate) -> SrcLatency {
+        src_latency(config, input.into(), output.into()).unwrap()
+    }
+
+    #[test]
+    fn same_rate() {
+        // 64 + 36 samples at 48 kHz.
+        assert_eq!(
+            latency(&SrcConfig::RESET, Rate::Hz48000, Rate::Hz48000),
+            SrcLatency {
+                output_samples: 100,
+                micros: 2083,
+            }
+        );
+    }
+
+    #[test]
+    fn low_latency_upsampling() {
+        let config = SrcConfig {
+            interpolation_group_delay: InterpolationFilterGroupDelay::_8,
+            direct_down_sampling: true,
+            ..SrcConfig::RESET
+        };
+        // 8 samples at 44.1 kHz is 181.4us, or 34.8 samples at 192 kHz.
+        assert_eq!(
+            latency(&config, Rate::Hz44100, Rate::Hz192000),
+            SrcLatency {
+                output_samples: 35,
+                micros: 181,
+            }
+        );
+    }
+
+    #[test]
+    fn zero_rate() {
+        assert_eq!(
+            src_latency(
+                &SrcConfig::RESET,
+                SampleRate::from_hz(0),
+                Rate::Hz48000.into()
+            ),
+            Err(ClockError::RateOutOfRange(SampleRate::from_hz(0)))
+        );
+    }
+
+    #[test]
+    fn src_latency_from_measured_ratio() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        let output = Rate::Hz48000.into();
+        assert!(matches!(
+            src4392.src_latency(&mut spi, output),
+            Err(Error::Clock(ClockError::RateOutOfRange(_)))
+        ));
+
+        sim.apply_event(Event::Lock { rate_hz: 96_000 });
+        assert_eq!(
+            src4392.src_latency(&mut spi, output).unwrap(),
+            SrcLatency {
+                output_samples: 68,
+                micros: 1417,
+            }
+        );
+
+        src4392
+            .set_src(
+                &mut spi,
+                SrcSource::DIR,
+                SrcClockSource::Mclk,
+                InterpolationFilterGroupDelay::_16,
+                Deemphasis::None,
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            src4392.src_latency(&mut spi, output).unwrap(),
+            SrcLatency {
+                output_samples: 44,
+                micros: 917,
+            }
+        );
+    }
+}
@@ -1,4 +1,6 @@
 #![no_std]
+#[cfg(any(test, feature = "std"))]
+extern crate std;
 use core::{fmt::Debug, marker::PhantomData};
 
 use embedded_hal::digital::v2::OutputPin;
@@ -12,25 +14,82 @@ pub use port_control::{
     AudioFormat, OutputDataSource, PortAControl1Register, PortAControl2Register,
     PortBControl1Register, PortBControl2Register, PortClockSource, PortMasterClockDivider,
 };
-use registers::Registers;
+use dump::{DumpError, RegisterDump};
+use registers::{Page, Registers};
 pub use sample_rate_converter::{
     Deemphasis, InterpolationFilterGroupDelay, SrcClockSource, SrcControl1, SrcControl2, SrcSource, SrcRatio,
 };
+pub use clock::{ClockError, ClockTree, Rate, RateFamily, RxckoUser, SampleRate};
+pub use config::{
+    ConfigError, DecodeError, DeviceConfig, DeviceConfigBuilder, PortConfig, PowerConfig, ReceiverConfig,
+    SrcConfig, TransmitterConfig,
+};
 
+pub mod clock;
+pub mod clock_failover;
+pub mod config;
+pub mod decode;
+pub mod dump;
+pub mod gpo;
 pub mod interrupt;
+pub mod latency;
+#[cfg(feature = "linux")]
+pub mod linux;
 pub mod port_control;
+#[cfg(feature = "serde")]
+pub mod profile;
+#[cfg(test)]
+mod proptests;
+pub mod rate_monitor;
+pub mod receiver;
+#[cfg(any(test, feature = "std"))]
+pub mod record;
 pub mod registers;
 pub mod reset;
 pub mod sample_rate_converter;
+pub mod src_follower;
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
+pub mod transmitter;
 
+use receiver::{ReceiverControl1, ReceiverStatus1, RecoveredClockDivider};
 use reset::Reset;
+use transmitter::{TransmitterClockDivider, TransmitterClockSource, TransmitterControl1};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Port {
     A,
     B,
 }
 
+/// The outcome of [`Src4392::probe`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProbeResult {
+    /// Test patterns written to the device were read back intact.
+    Ok,
+    /// Every read returned the same value, all zeros or all ones, whatever was written. Nothing
+    /// is driving the data out line.
+    NotPresent,
+    /// Something answers but the test patterns didn't come back intact, pointing at a shorted,
+    /// open or swapped line.
+    BusStuck,
+    /// The device answers but a register didn't hold its reset default after a reset.
+    BadResetDefault { register: Registers, value: u8 },
+}
+
+#[derive(Debug)]
+pub enum Error<E, R = Registers> {
+    Spi(E),
+    Config(ConfigError),
+    Decode(DecodeError),
+    Dump(DumpError),
+    Clock(ClockError),
+    /// A register read back after a write didn't hold the value written. Only returned when write
+    /// verification is enabled.
+    VerifyMismatch { register: R, wrote: u8, read: u8 },
+}
+
 pub struct Src4392<P, SPI, E, D, DT>
 where
     P: OutputPin,
@@ -42,7 +101,9 @@ where
     chip_select: P,
     spi: PhantomData<SPI>,
     delay: D,
-    delay_type: PhantomData<DT>
+    delay_type: PhantomData<DT>,
+    verify_writes: bool,
+    clocks: Option<ClockTree>,
 }
 
 impl<P, SPI, E, D, DT> Src4392<P, SPI, E, D, DT>
@@ -64,15 +125,44 @@ where
             chip_select,
             spi: PhantomData,
             delay,
-            delay_type: PhantomData
+            delay_type: PhantomData,
+            verify_writes: false,
+            clocks: None,
         }
     }
-    pub fn reset(&mut self, spi: &mut SPI) -> Result<(), E> {
-        self.modify_register(spi, |reg: &mut Reset| {
-            reg.reset = true;
-        })
+
+    /// Tells the driver the frequencies on the clock pins, which
+    /// [`Src4392::configure_port_for_rate`] needs to pick a divider.
+    pub fn set_clock_tree(&mut self, clocks: ClockTree) {
+        self.clocks = Some(clocks);
     }
 
+    pub fn clock_tree(&self) -> Option<&ClockTree> {
+        self.clocks.as_ref()
+    }
+
+    /// Enables or disables reading back every register after it is written. A register that
+    /// doesn't hold the written value causes an [`Error::VerifyMismatch`]. Reserved and self
+    /// clearing bits are ignored.
+    pub fn set_verify_writes(&mut self, enabled: bool) {
+        self.verify_writes = enabled;
+    }
+
+    /// Returns every register to its default. The reset also clears the power bits written
+    /// alongside it, so the write is never verified.
+    pub fn reset(&mut self, spi: &mut SPI) -> Result<(), Error<E>> {
+        let mut packed = Reset {
+            reset: true,
+            ..Default::default()
+        }
+        .pack()
+        .unwrap();
+        self.write_registers(spi, Reset::ADDRESS, &mut packed)
+            .map_err(Error::Spi)?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn configure_port(
         &mut self,
         spi: &mut SPI,
@@ -82,7 +172,7 @@ where
         clock_divider: PortMasterClockDivider,
         clock_source: PortClockSource,
         is_master: bool,
-    ) -> Result<(), E> {
+    ) -> Result<(), Error<E>> {
         match port {
             Port::A => {
                 self.modify_register(spi, |reg: &mut PortAControl1Register| {
@@ -113,6 +203,38 @@ where
         }
     }
 
+    /// Configures `port` as a master generating `rate` from `clock_source`. The divider is worked
+    /// out from the clock tree set with [`Src4392::set_clock_tree`].
+    ///
+    /// Returns [`ClockError::MissingClock`] when no clock tree is set or it doesn't know the
+    /// frequency of `clock_source`, and [`ClockError::NoDivider`] when the rate can't be reached.
+    /// The device isn't touched in either case.
+    pub fn configure_port_for_rate(
+        &mut self,
+        spi: &mut SPI,
+        port: Port,
+        audio_format: AudioFormat,
+        output_data_source: OutputDataSource,
+        rate: Rate,
+        clock_source: PortClockSource,
+    ) -> Result<(), Error<E>> {
+        let clock_hz = self
+            .clocks
+            .and_then(|clocks| clocks.source_hz(clock_source))
+            .ok_or(Error::Clock(ClockError::MissingClock(clock_source)))?;
+        let divider =
+            PortMasterClockDivider::for_rate(clock_hz, rate.into()).map_err(Error::Clock)?;
+        self.configure_port(
+            spi,
+            port,
+            audio_format,
+            output_data_source,
+            divider,
+            clock_source,
+            true,
+        )
+    }
+
     pub fn set_src(
         &mut self,
         spi: &mut SPI,
@@ -121,7 +243,7 @@ where
         interpolation_group_delay: InterpolationFilterGroupDelay,
         deemphasis: Deemphasis,
         is_direct_down_sampling: bool,
-    ) -> Result<(), E> {
+    ) -> Result<(), Error<E>> {
         self.modify_register(spi, |reg: &mut SrcControl1| {
             reg.source = src_source;
             reg.clock_source = clock_source;
@@ -136,12 +258,189 @@ where
         })
     }
 
+    /// Sets the DIT divider to transmit at `rate` from `clock_source`. The divider is worked out
+    /// from the clock tree set with [`Src4392::set_clock_tree`]. With `update_channel_status`
+    /// the sampling frequency in the outgoing channel status is set to `rate` as well, see
+    /// [`Src4392::set_transmitter_channel_status_rate`].
+    ///
+    /// Returns [`ClockError::MissingTransmitterClock`] when no clock tree is set or it doesn't
+    /// know the frequency of `clock_source`, and [`ClockError::NoDivider`] when the rate can't be
+    /// reached. The device isn't touched in either case.
+    pub fn configure_transmitter_for_rate(
+        &mut self,
+        spi: &mut SPI,
+        clock_source: TransmitterClockSource,
+        rate: Rate,
+        update_channel_status: bool,
+    ) -> Result<(), Error<E>> {
+        let clock_hz = self
+            .clocks
+            .and_then(|clocks| clocks.transmitter_source_hz(clock_source))
+            .ok_or(Error::Clock(ClockError::MissingTransmitterClock(clock_source)))?;
+        let divider =
+            TransmitterClockDivider::for_rate(clock_hz, rate.into()).map_err(Error::Clock)?;
+        self.modify_register(spi, |reg: &mut TransmitterControl1| {
+            reg.txclk = clock_source;
+            reg.txdiv = divider;
+        })?;
+        if update_channel_status {
+            self.set_transmitter_channel_status_rate(spi, rate)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the sampling frequency, bits 24 to 27 of IEC 60958-3 consumer channel status, in the
+    /// channel status the DIT sends on both channels. The rest of the channel status is kept.
+    ///
+    /// The DIT channel status buffer holds the left and right channel bytes interleaved, each
+    /// byte with its first bit in D0.
+    pub fn set_transmitter_channel_status_rate(
+        &mut self,
+        spi: &mut SPI,
+        rate: Rate,
+    ) -> Result<(), Error<E>> {
+        const BYTE_3: u8 = 3 * 2;
+        let mut status = [0u8; 2];
+        self.buffer_transfer(spi, Page::TransmitterBuffers, BYTE_3, &mut status, ReadWrite::Read)?;
+        for byte in status.iter_mut() {
+            *byte = (*byte & 0xF0) | rate.channel_status_code();
+        }
+        self.buffer_transfer(spi, Page::TransmitterBuffers, BYTE_3, &mut status, ReadWrite::Write)
+    }
+
+    /// Reads the ratio of the SRC input rate to its output rate, as measured by the SRC.
+    pub fn read_src_ratio(&mut self, spi: &mut SPI) -> Result<SrcRatio, Error<E>> {
+        let mut buffer = [0u8; 2];
+        self.read_registers(spi, SrcRatio::REGISTER_ADDRESS, &mut buffer)
+            .map_err(Error::Spi)?;
+        // Both fields are plain integers, so unpacking can't fail.
+        Ok(SrcRatio::unpack(&buffer).unwrap())
+    }
+
+    /// The delay through the SRC as it is set up now, converting to `output`. The input rate is
+    /// worked out from the ratio measured by the SRC.
+    ///
+    /// Returns [`ClockError::RateOutOfRange`] when the SRC has no input.
+    pub fn src_latency(
+        &mut self,
+        spi: &mut SPI,
+        output: SampleRate,
+    ) -> Result<latency::SrcLatency, Error<E>> {
+        let control: SrcControl2 = self.read_register(spi)?;
+        // A rate too large for a u32 is out of range all the same.
+        let input_hz = self
+            .read_src_ratio(spi)?
+            .input_hz(output.hz())
+            .unwrap_or(u32::MAX);
+        // Only the filter settings are needed.
+        let config = SrcConfig {
+            interpolation_group_delay: control.interpolation_group_delay,
+            direct_down_sampling: control.dd_n,
+            ..SrcConfig::RESET
+        };
+        latency::src_latency(&config, SampleRate::from_hz(input_hz), output)
+            .map_err(Error::Clock)
+    }
+
+    /// Enables RXCKO, the master clock recovered by the DIR divided by `divider`, and returns its
+    /// frequency while the DIR is locked to a stream at `input`. The rate can come from the
+    /// received channel status or from a [`rate_monitor::RateMonitor`].
+    ///
+    /// A clock tree set with [`Src4392::set_clock_tree`] is updated with the new frequency.
+    pub fn enable_recovered_clock(
+        &mut self,
+        spi: &mut SPI,
+        divider: RecoveredClockDivider,
+        input: SampleRate,
+    ) -> Result<u32, Error<E>> {
+        self.modify_register(spi, |reg: &mut ReceiverControl1| {
+            reg.rxckoe = true;
+            reg.rxckod = divider;
+        })?;
+        let rxcko_hz = ClockTree::recovered_clock_hz(input, divider);
+        if let Some(clocks) = self.clocks.as_mut() {
+            clocks.rxcko_hz = Some(rxcko_hz);
+        }
+        Ok(rxcko_hz)
+    }
+
+    /// Disables RXCKO, and removes it from the clock tree.
+    pub fn disable_recovered_clock(&mut self, spi: &mut SPI) -> Result<(), Error<E>> {
+        self.modify_register(spi, |reg: &mut ReceiverControl1| reg.rxckoe = false)?;
+        if let Some(clocks) = self.clocks.as_mut() {
+            clocks.rxcko_hz = None;
+        }
+        Ok(())
+    }
+
+    /// Checks that RXCKO is running if a master port or the SRC is clocked from it.
+    ///
+    /// Returns [`ClockError::RecoveredClockDisabled`] or [`ClockError::ReceiverUnlocked`] for the
+    /// first block found without a clock, ports before the SRC. Lock is read from
+    /// [`receiver::ReceiverStatus1`], so the unlock status should be in
+    /// [`interrupt::InterruptMode::Level`] for it to reflect the current state.
+    ///
+    /// Reading [`receiver::ReceiverStatus1`] clears every latched flag in it, so an interrupt
+    /// handler that hasn't read it yet loses them. Handlers should read the status themselves and
+    /// use [`Src4392::check_recovered_clock_with_status`] instead.
+    pub fn check_recovered_clock(&mut self, spi: &mut SPI) -> Result<(), Error<E>> {
+        self.check_recovered_clock_inner(spi, None)
+    }
+
+    /// Like [`Src4392::check_recovered_clock`], but takes lock from `status`, a
+    /// [`receiver::ReceiverStatus1`] the caller has already read, so no status flags are cleared.
+    pub fn check_recovered_clock_with_status(
+        &mut self,
+        spi: &mut SPI,
+        status: &ReceiverStatus1,
+    ) -> Result<(), Error<E>> {
+        self.check_recovered_clock_inner(spi, Some(status.unlock))
+    }
+
+    /// `unlock` is read from the device when it is `None`, and only if RXCKO is in use.
+    fn check_recovered_clock_inner(
+        &mut self,
+        spi: &mut SPI,
+        unlock: Option<bool>,
+    ) -> Result<(), Error<E>> {
+        let port_a1: PortAControl1Register = self.read_register(spi)?;
+        let port_a2: PortAControl2Register = self.read_register(spi)?;
+        let port_b1: PortBControl1Register = self.read_register(spi)?;
+        let port_b2: PortBControl2Register = self.read_register(spi)?;
+        let src: SrcControl1 = self.read_register(spi)?;
+        let user = if port_a1.am_slave && matches!(port_a2.aclk, PortClockSource::Rxcko) {
+            RxckoUser::Port(Port::A)
+        } else if port_b1.am_slave && matches!(port_b2.aclk, PortClockSource::Rxcko) {
+            RxckoUser::Port(Port::B)
+        } else if matches!(src.clock_source, SrcClockSource::Rxcko) {
+            RxckoUser::Src
+        } else {
+            return Ok(());
+        };
+
+        let receiver: ReceiverControl1 = self.read_register(spi)?;
+        if !receiver.rxckoe {
+            return Err(Error::Clock(ClockError::RecoveredClockDisabled(user)));
+        }
+        let unlock = match unlock {
+            Some(unlock) => unlock,
+            None => {
+                let status: ReceiverStatus1 = self.read_register(spi)?;
+                status.unlock
+            }
+        };
+        if unlock {
+            return Err(Error::Clock(ClockError::ReceiverUnlocked(user)));
+        }
+        Ok(())
+    }
+
     pub fn set_port_audio_format(
         &mut self,
         spi: &mut SPI,
         port: Port,
         audio_format: AudioFormat,
-    ) -> Result<(), E> {
+    ) -> Result<(), Error<E>> {
         match port {
             Port::A => self.modify_register(spi, |reg: &mut PortAControl1Register| {
                 reg.afmt = audio_format
@@ -151,6 +450,351 @@ where
             }),
         }
     }
+
+    /// Checks that the device answers on the SPI bus, then resets it and checks that it comes out
+    /// of reset as expected. **A device that answers is reset, which wipes its configuration**;
+    /// apply it again afterwards.
+    ///
+    /// Test patterns are written to the left channel SRC attenuation register and read back, then
+    /// the original attenuation is restored. When the patterns come back intact the device is
+    /// reset and the registers covered by [`DeviceConfig`] that have a
+    /// [`Registers::reset_value`] are checked against it. Verification of writes isn't used here,
+    /// the readbacks are classified into a [`ProbeResult`] instead.
+    pub fn probe(&mut self, spi: &mut SPI) -> Result<ProbeResult, Error<E>> {
+        const PATTERNS: [u8; 4] = [0x55, 0xAA, 0x0F, 0xF0];
+        let register = Registers::SrcControl4;
+
+        let mut original = [0u8];
+        self.read_registers(spi, register, &mut original)
+            .map_err(Error::Spi)?;
+
+        let mut reads = [0u8; PATTERNS.len()];
+        for (pattern, read) in PATTERNS.iter().zip(reads.iter_mut()) {
+            self.write_registers(spi, register, &mut [*pattern])
+                .map_err(Error::Spi)?;
+            let mut buffer = [0u8];
+            self.read_registers(spi, register, &mut buffer)
+                .map_err(Error::Spi)?;
+            *read = buffer[0];
+        }
+
+        self.write_registers(spi, register, &mut [original[0]])
+            .map_err(Error::Spi)?;
+
+        if reads != PATTERNS {
+            if reads.iter().chain(&original).all(|r| *r == 0x00)
+                || reads.iter().chain(&original).all(|r| *r == 0xFF)
+            {
+                return Ok(ProbeResult::NotPresent);
+            }
+            return Ok(ProbeResult::BusStuck);
+        }
+
+        self.reset(spi)?;
+        let mut image = [0u8; 21];
+        self.read_register_list(spi, &DeviceConfig::REGISTERS, &mut image)
+            .map_err(Error::Spi)?;
+        for (register, value) in DeviceConfig::REGISTERS.iter().zip(image) {
+            let Some(default) = register.reset_value() else {
+                continue;
+            };
+            if value & register.writable_bits() != default {
+                return Ok(ProbeResult::BadResetDefault {
+                    register: *register,
+                    value,
+                });
+            }
+        }
+        Ok(ProbeResult::Ok)
+    }
+
+    /// Writes a whole [`DeviceConfig`] to the device.
+    ///
+    /// The config is validated first, against the clock tree when one is set with
+    /// [`Src4392::set_clock_tree`]. Only registers that differ from the device are written. The
+    /// ports, SRC and transmitter are muted while the change is made, newly used blocks are
+    /// powered up before they are configured and unused blocks are powered down afterwards.
+    pub fn apply(&mut self, spi: &mut SPI, config: &DeviceConfig) -> Result<(), Error<E>> {
+        match &self.clocks {
+            Some(clocks) => config.validate_clocks(clocks),
+            None => config.validate(),
+        }
+        .map_err(Error::Config)?;
+
+        let target = config.to_image();
+        let mut current = [0u8; 21];
+        self.read_register_list(spi, &DeviceConfig::REGISTERS, &mut current)
+            .map_err(Error::Spi)?;
+        if target == current {
+            return Ok(());
+        }
+
+        let index_of = |register: Registers| {
+            DeviceConfig::REGISTERS
+                .iter()
+                .position(|r| *r == register)
+                .unwrap()
+        };
+        let mute_mask = |register: Registers| {
+            DeviceConfig::MUTES
+                .iter()
+                .find(|(r, _)| *r == register)
+                .map_or(0, |(_, mask)| *mask)
+        };
+
+        for (register, mask) in DeviceConfig::MUTES {
+            let index = index_of(register);
+            if current[index] & mask == 0 {
+                current[index] |= mask;
+                self.write_register(spi, register, current[index])?;
+            }
+        }
+
+        let power_up = current[0] | target[0];
+        if power_up != current[0] {
+            current[0] = power_up;
+            self.write_register(spi, Registers::Reset, power_up)?;
+        }
+
+        for (index, register) in DeviceConfig::REGISTERS.iter().enumerate().skip(1) {
+            let value = target[index] | mute_mask(*register);
+            if value != current[index] {
+                current[index] = value;
+                self.write_register(spi, *register, value)?;
+            }
+        }
+
+        if target[0] != current[0] {
+            self.write_register(spi, Registers::Reset, target[0])?;
+        }
+
+        for (register, _) in DeviceConfig::MUTES {
+            let index = index_of(register);
+            if target[index] != current[index] {
+                self.write_register(spi, register, target[index])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes an image built with [`DeviceConfig::to_image`] straight to the device, bursting over
+    /// runs of consecutive registers.
+    ///
+    /// Nothing is read, checked or muted first, which keeps the code small and the setup fast.
+    /// It is meant for a device that was just reset, with an image checked by
+    /// [`DeviceConfig::validate`], which happens at compile time for a `const` config. Use
+    /// [`Src4392::apply`] to change a running device.
+    pub fn load_image(&mut self, spi: &mut SPI, image: &[u8; 21]) -> Result<(), Error<E>> {
+        let registers = &DeviceConfig::REGISTERS;
+        let mut buffer = *image;
+        let mut start = 0;
+        while start < registers.len() {
+            let mut end = start + 1;
+            while end < registers.len() && registers[end] as u8 == registers[end - 1] as u8 + 1 {
+                end += 1;
+            }
+            self.write_registers(spi, registers[start], &mut buffer[start..end])
+                .map_err(Error::Spi)?;
+            start = end;
+        }
+
+        if self.verify_writes {
+            let mut read = [0u8; 21];
+            self.read_register_list(spi, registers, &mut read)
+                .map_err(Error::Spi)?;
+            for (index, register) in registers.iter().enumerate() {
+                let checked = verified_bits(*register);
+                if image[index] & checked != read[index] & checked {
+                    return Err(Error::VerifyMismatch {
+                        register: *register,
+                        wrote: image[index],
+                        read: read[index],
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the current setup of the device. The reads are burst over runs of consecutive
+    /// control registers, status registers are never read.
+    pub fn read_config(&mut self, spi: &mut SPI) -> Result<DeviceConfig, Error<E>> {
+        let mut image = [0u8; 21];
+        self.read_register_list(spi, &DeviceConfig::REGISTERS, &mut image)
+            .map_err(Error::Spi)?;
+        DeviceConfig::from_image(&image).map_err(Error::Decode)
+    }
+
+    /// Reads every page 0 register into `out` using the layout described in [`dump`], and
+    /// returns the number of bytes written.
+    ///
+    /// The DIR and DIT buffer pages are included when `N` is at least [`dump::FULL_DUMP_LEN`].
+    /// `N` must be at least [`dump::REGISTER_DUMP_LEN`].
+    ///
+    /// The status registers are read too, which clears the interrupt flags latched in them, as
+    /// any read of a status register does. Service pending interrupts before taking a dump.
+    pub fn dump<const N: usize>(
+        &mut self,
+        spi: &mut SPI,
+        out: &mut [u8; N],
+    ) -> Result<usize, Error<E>> {
+        if N < dump::REGISTER_DUMP_LEN {
+            return Err(Error::Dump(DumpError::TooShort));
+        }
+        let buffers = N >= dump::FULL_DUMP_LEN;
+        out.fill(0);
+        dump::write_header(out, buffers);
+
+        let first = Registers::Reset as usize;
+        self.write_register(spi, Registers::PageSelection, Page::Control as u8)?;
+        self.read_registers(
+            spi,
+            Registers::Reset,
+            &mut out[dump::HEADER_LEN + first..dump::REGISTER_DUMP_LEN],
+        )
+        .map_err(Error::Spi)?;
+
+        if !buffers {
+            return Ok(dump::REGISTER_DUMP_LEN);
+        }
+        let (receiver, transmitter) =
+            out[dump::REGISTER_DUMP_LEN..dump::FULL_DUMP_LEN].split_at_mut(dump::BUFFER_PAGE_LEN);
+        self.buffer_transfer(spi, Page::ReceiverBuffers, 0, receiver, ReadWrite::Read)?;
+        self.buffer_transfer(spi, Page::TransmitterBuffers, 0, transmitter, ReadWrite::Read)?;
+        Ok(dump::FULL_DUMP_LEN)
+    }
+
+    /// Writes a dump taken with [`Src4392::dump`] back to the device.
+    ///
+    /// Only writable control registers are restored, status and read only addresses are
+    /// skipped. Power and the registers covered by [`DeviceConfig`] are written first, in the
+    /// order of [`DeviceConfig::REGISTERS`], then the interrupt setup. The reset bit is never
+    /// written. If the dump holds the buffer pages the DIT buffers are restored too; the DIR
+    /// buffers hold received data and are left alone.
+    pub fn restore(&mut self, spi: &mut SPI, dump: &[u8]) -> Result<(), Error<E>> {
+        let dump = RegisterDump::parse(dump).map_err(Error::Dump)?;
+
+        // The device may have been left on a buffer page, for example by an interrupted transfer.
+        self.write_register(spi, Registers::PageSelection, Page::Control as u8)?;
+
+        let remaining = Registers::ALL
+            .iter()
+            .filter(|r| r.is_writable() && !DeviceConfig::REGISTERS.contains(r));
+        for register in DeviceConfig::REGISTERS.iter().chain(remaining) {
+            let mut value = dump.register(*register);
+            if *register == Registers::Reset {
+                value &= !0b1000_0000;
+            }
+            self.write_register(spi, *register, value)?;
+        }
+
+        if let Some(buffers) = dump.transmitter_buffers() {
+            let mut buffer = [0u8; dump::BUFFER_PAGE_LEN];
+            buffer.copy_from_slice(buffers);
+            self.buffer_transfer(
+                spi,
+                Page::TransmitterBuffers,
+                0,
+                &mut buffer,
+                ReadWrite::Write,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Transfers `buffer` to or from `page`, starting from address `start`. Page 0 is selected
+    /// again afterwards.
+    fn buffer_transfer(
+        &mut self,
+        spi: &mut SPI,
+        page: Page,
+        start: u8,
+        buffer: &mut [u8],
+        read_or_write: ReadWrite,
+    ) -> Result<(), Error<E>> {
+        self.write_register(spi, Registers::PageSelection, page as u8)?;
+        self.assert_cs();
+        let mut cmd_bytes = [
+            match read_or_write {
+                ReadWrite::Read => 0b10000000 | start,
+                ReadWrite::Write => start,
+            },
+            0u8,
+        ];
+        let result = spi
+            .transfer(&mut cmd_bytes)
+            .and_then(|_| spi.transfer(buffer).map(|_| ()));
+        self.deassert_cs();
+        result.map_err(Error::Spi)?;
+        self.write_register(spi, Registers::PageSelection, Page::Control as u8)
+    }
+
+    fn write_register(
+        &mut self,
+        spi: &mut SPI,
+        register: Registers,
+        value: u8,
+    ) -> Result<(), Error<E>> {
+        self.write_registers(spi, register, &mut [value])
+            .map_err(Error::Spi)?;
+        if self.verify_writes {
+            let mut read = [0u8];
+            self.read_registers(spi, register, &mut read)
+                .map_err(Error::Spi)?;
+            let checked = verified_bits(register);
+            if value & checked != read[0] & checked {
+                return Err(Error::VerifyMismatch {
+                    register,
+                    wrote: value,
+                    read: read[0],
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a single byte register.
+    pub(crate) fn read_register<R>(&mut self, spi: &mut SPI) -> Result<R, Error<E>>
+    where
+        R: PackedStruct<ByteArray = [u8; 1]> + RegisterAddress<Registers>,
+    {
+        let mut buffer = [0u8];
+        self.read_registers(spi, R::register_address(), &mut buffer)
+            .map_err(Error::Spi)?;
+        // Register types have a variant for every enum code, so unpacking can't fail.
+        Ok(R::unpack(&buffer).unwrap())
+    }
+
+    /// Reads each register in `registers` into `values`, bursting over runs of consecutive
+    /// addresses.
+    fn read_register_list(
+        &mut self,
+        spi: &mut SPI,
+        registers: &[Registers],
+        values: &mut [u8],
+    ) -> Result<(), E> {
+        let mut start = 0;
+        while start < registers.len() {
+            let mut end = start + 1;
+            while end < registers.len() && registers[end] as u8 == registers[end - 1] as u8 + 1 {
+                end += 1;
+            }
+            self.read_registers(spi, registers[start], &mut values[start..end])?;
+            start = end;
+        }
+        Ok(())
+    }
+}
+
+/// The bits of `register` that hold what was written. Reserved, read only and self clearing bits
+/// are left out of write verification.
+fn verified_bits(register: Registers) -> u8 {
+    let self_clearing = match register {
+        Registers::Reset => Reset::self_clearing_bits()[0],
+        _ => 0,
+    };
+    register.writable_bits() & !self_clearing
 }
 
 impl<P, SPI, E, D, DT> ReadModifyWriteSpiRegister<SPI, E, Registers> for Src4392<P, SPI, E, D, DT>
@@ -169,9 +813,13 @@ where
         self.chip_select.set_high().unwrap();
         self.delay.delay_ms(1.into());
     }
+
+    fn verify_writes(&self) -> bool {
+        self.verify_writes
+    }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ReadWrite {
     Read,
     Write,
@@ -181,6 +829,12 @@ pub enum ReadWrite {
 /// it corresponds to.
 pub trait RegisterAddress<R> {
     fn register_address() -> R;
+
+    /// Bits that clear themselves once written, one mask per register byte. They are ignored
+    /// when verifying writes.
+    fn self_clearing_bits() -> &'static [u8] {
+        &[]
+    }
 }
 
 /// Implement this trait on your Registers enum. Often you will set a bit in part of the address to
@@ -227,26 +881,187 @@ where
         result
     }
 
+    /// Reads the register, lets `f` change it and writes it back.
+    ///
+    /// When [`ReadModifyWriteSpiRegister::verify_writes`] is true the register is read again after
+    /// the write and compared with the value written. Reserved bits, which unpack to nothing, and
+    /// [`RegisterAddress::self_clearing_bits`] are ignored in the comparison.
     fn modify_register<F, R, const RSIZE: usize>(
         &mut self,
         spi: &mut SPI,
         mut f: F,
-    ) -> Result<(), SPIERROR>
+    ) -> Result<(), Error<SPIERROR, REGISTERS>>
     where
         F: FnMut(&mut R),
         R: PackedStruct<ByteArray = [u8; RSIZE]> + RegisterAddress<REGISTERS>,
     {
         let mut buffer = [0u8; RSIZE];
-        self.read_registers(spi, R::register_address(), &mut buffer)?;
+        self.read_registers(spi, R::register_address(), &mut buffer)
+            .map_err(Error::Spi)?;
+        // Register types have a variant for every enum code, so unpacking can't fail.
         let mut unpacked = R::unpack(&buffer).unwrap();
         f(&mut unpacked);
-        let mut packed = unpacked.pack().unwrap();
+        let wrote = unpacked.pack().unwrap();
+        let mut packed = wrote;
+
+        self.write_registers(spi, R::register_address(), &mut packed)
+            .map_err(Error::Spi)?;
 
-        self.write_registers(spi, R::register_address(), &mut packed)?;
+        if self.verify_writes() {
+            let mut read = [0u8; RSIZE];
+            self.read_registers(spi, R::register_address(), &mut read)
+                .map_err(Error::Spi)?;
+            let normalised = R::unpack(&read)
+                .and_then(|r| r.pack())
+                .unwrap_or(read);
+            for (index, (wrote, normalised)) in wrote.iter().zip(normalised).enumerate() {
+                let ignored = R::self_clearing_bits().get(index).copied().unwrap_or(0);
+                if wrote & !ignored != normalised & !ignored {
+                    return Err(Error::VerifyMismatch {
+                        register: R::register_address(),
+                        wrote: *wrote,
+                        read: read[index],
+                    });
+                }
+            }
+        }
 
         Ok(())
     }
 
+    /// Whether [`ReadModifyWriteSpiRegister::modify_register`] reads back and checks every write.
+    fn verify_writes(&self) -> bool {
+        false
+    }
+
     fn assert_cs(&mut self);
     fn deassert_cs(&mut self);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump::{HEADER_LEN, REGISTER_DUMP_LEN};
+    use crate::sim::fixture::{config, device};
+    use crate::sim::{NoDelay, SimulatedSrc4392};
+    use core::convert::Infallible;
+
+    #[test]
+    fn verify_ignores_reserved_bits() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        let mut dump = [0u8; REGISTER_DUMP_LEN];
+        src4392.dump(&mut spi, &mut dump).unwrap();
+        // Bit 7 is reserved and reads back as zero.
+        dump[HEADER_LEN + Registers::PortAControl as usize] = 0xFF;
+
+        src4392.set_verify_writes(true);
+        src4392.restore(&mut spi, &dump).unwrap();
+        assert_eq!(sim.register(Registers::PortAControl), 0x7F);
+    }
+
+    #[test]
+    fn verify_reports_bits_that_did_not_stick() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        let mut dump = [0u8; REGISTER_DUMP_LEN];
+        src4392.dump(&mut spi, &mut dump).unwrap();
+        dump[HEADER_LEN + Registers::PortAControl as usize] = 0x7F;
+        sim.set_stuck_low(Registers::PortAControl, 0b0000_1000);
+
+        src4392.set_verify_writes(true);
+        match src4392.restore(&mut spi, &dump) {
+            Err(Error::VerifyMismatch {
+                register,
+                wrote,
+                read,
+            }) => {
+                assert_eq!(register, Registers::PortAControl);
+                assert_eq!((wrote, read), (0x7F, 0x77));
+            }
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn modify_register_reports_bits_that_did_not_stick() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        sim.set_stuck_low(Registers::PortAControl, 0b0000_1000);
+
+        let mut configure = |verify| {
+            src4392.set_verify_writes(verify);
+            src4392.configure_port(
+                &mut spi,
+                Port::A,
+                AudioFormat::I2S,
+                OutputDataSource::SRC,
+                PortMasterClockDivider::_256,
+                PortClockSource::Mclk,
+                true,
+            )
+        };
+        // Without verification the fault goes unnoticed.
+        configure(false).unwrap();
+
+        match configure(true) {
+            Err(Error::VerifyMismatch {
+                register,
+                wrote,
+                read,
+            }) => {
+                assert_eq!(register, Registers::PortAControl);
+                assert_eq!(wrote & 0b0000_1000, 0b0000_1000);
+                assert_eq!(read & 0b0000_1000, 0);
+            }
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reset_self_clears() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        src4392.set_verify_writes(true);
+        src4392.apply(&mut spi, &config()).unwrap();
+        src4392.reset(&mut spi).unwrap();
+        assert_eq!(sim.register(Registers::Reset), 0);
+        assert_eq!(sim.register(Registers::PortAControl), 0);
+    }
+
+    #[test]
+    fn probe() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        src4392.apply(&mut spi, &config()).unwrap();
+        assert_eq!(src4392.probe(&mut spi).unwrap(), ProbeResult::Ok);
+        // A device that passes is left reset.
+        assert_eq!(
+            src4392.read_config(&mut spi).unwrap(),
+            DeviceConfig::default()
+        );
+
+        struct Floating(u8);
+        impl Transfer<u8> for Floating {
+            type Error = Infallible;
+            fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+                words.fill(self.0);
+                Ok(words)
+            }
+        }
+        let mut src4392: Src4392<_, Floating, _, _, u8> = Src4392::new(&sim, NoDelay);
+        assert_eq!(
+            src4392.probe(&mut Floating(0xFF)).unwrap(),
+            ProbeResult::NotPresent
+        );
+    }
+}
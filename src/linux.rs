@@ -0,0 +1,92 @@
+//! Running the driver from Linux, over spidev and a GPIO chip select.
+//!
+//! ```no_run
+//! use src4392::linux::{self, ChipSelect, Delay};
+//! use src4392::Src4392;
+//!
+//! let mut spi = linux::open_spi("/dev/spidev0.0", 1_000_000).unwrap();
+//! let chip_select = ChipSelect::cdev("/dev/gpiochip0", 8).unwrap();
+//! let mut src4392: linux::LinuxSrc4392 = Src4392::new(chip_select, Delay);
+//! src4392.reset(&mut spi).unwrap();
+//! ```
+
+use std::io;
+use std::path::Path;
+
+use embedded_hal::digital::v2::OutputPin;
+use linux_embedded_hal::gpio_cdev::{Chip, LineRequestFlags};
+use linux_embedded_hal::spidev::{SpiModeFlags, SpidevOptions};
+use linux_embedded_hal::sysfs_gpio::Direction;
+pub use linux_embedded_hal::{gpio_cdev, sysfs_gpio, CdevPin, Delay, Spidev, SysfsPin};
+
+use crate::Src4392;
+
+/// The driver over spidev, with the chip select on a GPIO.
+pub type LinuxSrc4392 = Src4392<ChipSelect, Spidev, io::Error, Delay, u8>;
+
+#[derive(Debug)]
+pub enum ChipSelectError {
+    Cdev(gpio_cdev::Error),
+    Sysfs(sysfs_gpio::Error),
+}
+
+/// A chip select driven through either GPIO interface.
+pub enum ChipSelect {
+    Cdev(CdevPin),
+    Sysfs(SysfsPin),
+}
+
+impl ChipSelect {
+    /// Requests `line` of the GPIO character device at `chip` as an output, driven high.
+    pub fn cdev(chip: impl AsRef<Path>, line: u32) -> Result<Self, ChipSelectError> {
+        let handle = Chip::new(chip)
+            .and_then(|mut chip| chip.get_line(line))
+            .and_then(|line| line.request(LineRequestFlags::OUTPUT, 1, "src4392"))
+            .map_err(ChipSelectError::Cdev)?;
+        CdevPin::new(handle)
+            .map(ChipSelect::Cdev)
+            .map_err(ChipSelectError::Cdev)
+    }
+
+    /// Exports `pin` through sysfs and makes it an output, driven high.
+    pub fn sysfs(pin: u64) -> Result<Self, ChipSelectError> {
+        let pin = SysfsPin::new(pin);
+        pin.0
+            .export()
+            .and_then(|_| pin.0.set_direction(Direction::High))
+            .map_err(ChipSelectError::Sysfs)?;
+        Ok(ChipSelect::Sysfs(pin))
+    }
+}
+
+impl OutputPin for ChipSelect {
+    type Error = ChipSelectError;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        match self {
+            ChipSelect::Cdev(pin) => pin.set_low().map_err(ChipSelectError::Cdev),
+            ChipSelect::Sysfs(pin) => pin.set_low().map_err(ChipSelectError::Sysfs),
+        }
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        match self {
+            ChipSelect::Cdev(pin) => pin.set_high().map_err(ChipSelectError::Cdev),
+            ChipSelect::Sysfs(pin) => pin.set_high().map_err(ChipSelectError::Sysfs),
+        }
+    }
+}
+
+/// Opens the spidev device at `path` in [`Src4392::SPI_MODE`] with 8 bit words. The chip select
+/// is driven by [`ChipSelect`], so the controller's own is turned off.
+pub fn open_spi(path: impl AsRef<Path>, max_speed_hz: u32) -> io::Result<Spidev> {
+    let mut spi = Spidev::open(path)?;
+    spi.0.configure(
+        &SpidevOptions::new()
+            .bits_per_word(8)
+            .max_speed_hz(max_speed_hz)
+            .mode(SpiModeFlags::SPI_MODE_3 | SpiModeFlags::SPI_NO_CS)
+            .build(),
+    )?;
+    Ok(spi)
+}
@@ -3,10 +3,13 @@ use packed_struct::prelude::*;
 use crate::{registers::Registers, RegisterAddress};
 
 #[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AudioFormat {
     #[default]
     LeftJustified24bit = 0b000,
     I2S = 0b001,
+    Reserved010 = 0b010,
+    Reserved011 = 0b011,
     RightJustified16bit = 0b100,
     RightJustified18bit = 0b101,
     RightJustified20bit = 0b110,
@@ -14,6 +17,7 @@ pub enum AudioFormat {
 }
 
 #[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OutputDataSource {
     #[default]
     Loopback = 0b00,
@@ -23,22 +27,30 @@ pub enum OutputDataSource {
 }
 
 #[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PortMasterClockDivider {
     #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "128"))]
     _128 = 0b00,
+    #[cfg_attr(feature = "serde", serde(rename = "256"))]
     _256 = 0b01,
+    #[cfg_attr(feature = "serde", serde(rename = "384"))]
     _384 = 0b10,
+    #[cfg_attr(feature = "serde", serde(rename = "512"))]
     _512 = 0b11,
 }
 #[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PortClockSource {
     #[default]
     Mclk = 0b00,
     Rxcki = 0b01,
     Rxcko = 0b10,
+    Reserved = 0b11,
 }
 
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
 pub struct PortAControl1Register {
     #[packed_field(bits = "0..3", ty = "enum")]
@@ -56,6 +68,7 @@ pub struct PortAControl1Register {
     #[packed_field(bits = "6")]
     pub amute: bool,
     #[packed_field(bits = "7")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _reserved: ReservedZero<packed_bits::Bits<1>>,
 }
 
@@ -70,13 +83,15 @@ impl RegisterAddress<Registers> for PortAControl1Register {
 }
 
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
 pub struct PortAControl2Register {
     #[packed_field(bits = "0..2", ty = "enum")]
     pub adiv: PortMasterClockDivider,
     #[packed_field(bits = "2..4", ty = "enum")]
     pub aclk: PortClockSource,
-    #[packed_field(bits = "4..7")]
+    #[packed_field(bits = "4..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _reserved: ReservedZeroes<packed_bits::Bits<4>>,
 }
 impl PortAControl2Register {
@@ -90,6 +105,7 @@ impl RegisterAddress<Registers> for PortAControl2Register {
 }
 
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
 pub struct PortBControl1Register {
     #[packed_field(bits = "0..3", ty = "enum")]
@@ -107,6 +123,7 @@ pub struct PortBControl1Register {
     #[packed_field(bits = "6")]
     pub amute: bool,
     #[packed_field(bits = "7")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _reserved: ReservedZero<packed_bits::Bits<1>>,
 }
 impl PortBControl1Register {
@@ -120,13 +137,15 @@ impl RegisterAddress<Registers> for PortBControl1Register {
 }
 
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
 pub struct PortBControl2Register {
     #[packed_field(bits = "0..2", ty = "enum")]
     pub adiv: PortMasterClockDivider,
     #[packed_field(bits = "2..4", ty = "enum")]
     pub aclk: PortClockSource,
-    #[packed_field(bits = "4..7")]
+    #[packed_field(bits = "4..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _reserved: ReservedZeroes<packed_bits::Bits<4>>,
 }
 impl PortBControl2Register {
@@ -0,0 +1,159 @@
+//! Whole device profiles.
+//!
+//! A profile is a serialized [`DeviceConfig`]. Every table and key is optional. Anything left out
+//! keeps its reset value. Unknown keys are rejected, so a misspelt setting can't be dropped
+//! silently. Enum settings use the variant names of the Rust types. Dividers, group delays and
+//! word lengths use their number as a string, for example `"256"` or `"24bits"`, and de-emphasis
+//! uses `"48000Hz"`, `"44100Hz"` or `"32000Hz"`.
+//!
+//! ```toml
+//! # Tables: port_a, port_b, src, receiver, receiver.pll, transmitter and power. `gpo` is an
+//! # array of four GpoFunction names.
+//! gpo = ["Low", "ReceiverInterrupt", "Low", "Low"]
+//!
+//! [power]
+//! port_a = true
+//! port_b = true
+//! receiver = true
+//! src = true
+//!
+//! [port_a]
+//! audio_format = "I2S"                # AudioFormat
+//! output_data_source = "DIR"          # OutputDataSource
+//! clock_divider = "256"               # PortMasterClockDivider
+//! clock_source = "Mclk"               # PortClockSource
+//! is_master = true
+//! mute = false
+//!
+//! [port_b]
+//! audio_format = "I2S"
+//! output_data_source = "SRC"
+//! is_master = true
+//!
+//! [src]
+//! source = "DIR"                      # SrcSource
+//! clock_source = "Mclk"               # SrcClockSource
+//! interpolation_group_delay = "64"    # InterpolationFilterGroupDelay
+//! deemphasis = "None"                 # Deemphasis
+//! auto_deemphasis = false
+//! direct_down_sampling = false
+//! track = false
+//! mute = false
+//! output_word_length = "24bits"       # SrcOutputWordLength
+//! attenuation_left = 0
+//! attenuation_right = 0
+//! nominal_rates = [44100, 48000]      # Input and output rate in Hz, only used for checks.
+//!
+//! [receiver]
+//! input = "Rx1"                       # ReceiverInput
+//! reference_clock = "Mclk"            # ReceiverReferenceClock
+//! recovered_clock_output = false
+//! recovered_clock_divider = "1"       # RecoveredClockDivider
+//! auto_mute_on_loss_of_lock = true
+//! zero_on_loss_of_lock = false
+//! buffer_transfer_disabled = false
+//!
+//! [receiver.pll]
+//! p = 2
+//! j = 8
+//! d = 0
+//!
+//! [transmitter]
+//! input = "PortA"                     # TransmitterInputSource
+//! clock_source = "Mclk"               # TransmitterClockSource
+//! clock_divider = "128"               # TransmitterClockDivider
+//! validity = false
+//! mute = false
+//! line_driver_off = false
+//! aes_output_off = false
+//! line_driver_bypass = false
+//! aes_output_bypass = false
+//! bypass_input = "Rx1"                # BypassMultiplexer
+//! block_start_input = false
+//! block_start_mode = false
+//! buffer_transfer_disabled = false
+//! ```
+//!
+//! Host tools read the TOML form with [`from_toml`], which needs the `toml` feature. Firmware can
+//! embed a profile with any serde format that works without std, or as a [`DeviceConfig`]
+//! constant. Either way [`crate::Src4392::apply`] checks it again and writes it.
+
+use crate::config::{ConfigError, DeviceConfig};
+
+#[derive(Debug)]
+pub enum ProfileError {
+    /// The text isn't a valid profile.
+    #[cfg(feature = "toml")]
+    Parse(toml::de::Error),
+    /// The profile parsed but describes a setup the device can't run.
+    Config(ConfigError),
+}
+
+/// Checks a parsed profile with [`DeviceConfig::validate`].
+pub fn load(config: DeviceConfig) -> Result<DeviceConfig, ProfileError> {
+    config.validate().map_err(ProfileError::Config)?;
+    Ok(config)
+}
+
+/// Parses and checks a profile in TOML form.
+#[cfg(feature = "toml")]
+pub fn from_toml(text: &str) -> Result<DeviceConfig, ProfileError> {
+    load(toml::from_str(text).map_err(ProfileError::Parse)?)
+}
+
+/// Writes a profile in TOML form.
+#[cfg(feature = "toml")]
+pub fn to_toml(config: &DeviceConfig) -> std::string::String {
+    toml::to_string(config).expect("every DeviceConfig is representable in TOML")
+}
+
+#[cfg(all(test, feature = "toml"))]
+mod tests {
+    use super::*;
+    use crate::port_control::{AudioFormat, OutputDataSource, PortMasterClockDivider};
+    use crate::sample_rate_converter::SrcSource;
+
+    #[test]
+    fn documented_example() {
+        let text = include_str!("profile.rs")
+            .lines()
+            .skip_while(|line| *line != "//! ```toml")
+            .skip(1)
+            .take_while(|line| *line != "//! ```")
+            .map(|line| line.trim_start_matches("//!").trim_start())
+            .collect::<std::vec::Vec<_>>()
+            .join("\n");
+
+        let config = from_toml(&text).unwrap();
+        assert_eq!(config.port_a.audio_format, AudioFormat::I2S);
+        assert_eq!(config.port_a.clock_divider, PortMasterClockDivider::_256);
+        assert_eq!(config.port_b.output_data_source, OutputDataSource::SRC);
+        assert_eq!(config.src.source, SrcSource::DIR);
+        assert_eq!(config.src.nominal_rates, Some((44100, 48000)));
+        assert_eq!(config.receiver.pll.p, 2);
+        assert!(config.power.src);
+
+        assert_eq!(from_toml(&to_toml(&config)).unwrap(), config);
+    }
+
+    #[test]
+    fn empty_profile_is_reset_state() {
+        assert_eq!(from_toml("").unwrap(), DeviceConfig::default());
+    }
+
+    #[test]
+    fn rejects_bad_profiles() {
+        assert!(matches!(
+            from_toml("[port_a]\nformat = \"I2S\""),
+            Err(ProfileError::Parse(_))
+        ));
+        assert!(matches!(
+            from_toml("[port_a]\nclock_divider = \"100\""),
+            Err(ProfileError::Parse(_))
+        ));
+        assert!(matches!(
+            from_toml("[src]\nsource = \"Reserved\""),
+            Err(ProfileError::Config(ConfigError::ReservedSetting(_)))
+        ));
+    }
+}
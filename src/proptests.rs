@@ -0,0 +1,443 @@
+//! Property tests for every register type.
+//!
+//! Each register is described by its datasheet layout, the position and width of every field.
+//! Values are generated from the layout, so these tests check the `packed_field` attributes
+//! against the datasheet rather than against themselves. Enum fields have a variant for every
+//! code, reserved ones included, so any value of a field is valid.
+
+use packed_struct::prelude::*;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::vec::Vec;
+
+use crate::config::DeviceConfig;
+use crate::decode::decode_any;
+use crate::gpo::GpoRegister;
+use crate::interrupt::{
+    GlobalInterruptStatus, SrcAndDitInterruptMask, SrcAndDitInterruptMode, SrcAndDitStatus,
+};
+use crate::receiver::{
+    NonPcmAudioDetection, ReceiverControl1, ReceiverControl2, ReceiverInterruptMask1,
+    ReceiverInterruptMask2, ReceiverInterruptMode1, ReceiverInterruptMode2, ReceiverInterruptMode3,
+    ReceiverPllConfiguration, ReceiverStatus1, ReceiverStatus2,
+};
+use crate::reset::Reset;
+use crate::sample_rate_converter::{SrcControl3, SrcControl4, SrcControl5};
+use crate::transmitter::{TransmitterControl1, TransmitterControl2, TransmitterControl3};
+use crate::{
+    PortAControl1Register, PortAControl2Register, PortBControl1Register, PortBControl2Register,
+    SrcControl1, SrcControl2, SrcRatio,
+};
+
+/// A field `width` bits wide starting at bit `lsb`, counting from the least significant bit of
+/// the last register byte.
+struct Field {
+    lsb: u32,
+    width: u32,
+}
+
+const fn bit(lsb: u32) -> Field {
+    bits(lsb, 1)
+}
+
+const fn bits(lsb: u32, width: u32) -> Field {
+    Field { lsb, width }
+}
+
+fn mask(field: &Field) -> u64 {
+    ((1u64 << field.width) - 1) << field.lsb
+}
+
+/// Builds the raw register value from one generated value per field, returning it with the field
+/// values used.
+fn compose(layout: &[Field], values: &[u64]) -> (u64, Vec<u64>) {
+    let mut raw = 0;
+    let mut fields = Vec::new();
+    for (field, value) in layout.iter().zip(values) {
+        let value = value & (mask(field) >> field.lsb);
+        raw |= value << field.lsb;
+        fields.push(value);
+    }
+    (raw, fields)
+}
+
+fn to_bytes<const N: usize>(raw: u64) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = (raw >> (8 * (N - 1 - index))) as u8;
+    }
+    bytes
+}
+
+/// Checks that `values` composed through `layout` unpack into the expected fields, pack back to
+/// the same bytes and that set reserved bits unpack and are packed as zero.
+fn check<R, const N: usize>(
+    layout: &[Field],
+    fields: fn(&R) -> Vec<u64>,
+    values: &[u64],
+    noise: u64,
+) -> Result<(), TestCaseError>
+where
+    R: PackedStruct<ByteArray = [u8; N]>,
+{
+    let (raw, expected) = compose(layout, values);
+    let used = layout.iter().fold(0, |used, field| used | mask(field));
+
+    let bytes = to_bytes::<N>(raw);
+    let unpacked = R::unpack(&bytes).unwrap();
+    prop_assert_eq!(fields(&unpacked), expected.clone());
+    prop_assert_eq!(unpacked.pack().unwrap(), bytes);
+
+    let width = 8 * N as u32;
+    let reserved = !used & (u64::MAX >> (64 - width));
+    let noisy = to_bytes::<N>(raw | (noise & reserved));
+    let unpacked = R::unpack(&noisy).unwrap();
+    prop_assert_eq!(fields(&unpacked), expected);
+    prop_assert_eq!(unpacked.pack().unwrap(), bytes);
+    Ok(())
+}
+
+macro_rules! register_proptest {
+    ($name:ident, $register:ty, $bytes:literal, [$($field:expr),* $(,)?], |$r:ident| [$($value:expr),* $(,)?]) => {
+        proptest! {
+            #[test]
+            fn $name(values in vec(any::<u64>(), [$($field),*].len()), noise in any::<u64>()) {
+                check::<$register, $bytes>(
+                    &[$($field),*],
+                    |$r: &$register| std::vec![$($value as u64),*],
+                    &values,
+                    noise,
+                )?;
+            }
+        }
+    };
+}
+
+register_proptest!(
+    reset,
+    Reset,
+    1,
+    [bit(0), bit(1), bit(2), bit(3), bit(4), bit(5), bit(7)],
+    |r| [r.n_pdsrc, r.n_pdrx, r.n_pdtx, r.n_pdpb, r.n_pdpa, r.n_pdall, r.reset]
+);
+
+register_proptest!(
+    port_a_control1,
+    PortAControl1Register,
+    1,
+    [bits(0, 3), bit(3), bits(4, 2), bit(6)],
+    |r| [
+        r.afmt.to_primitive(),
+        r.am_slave,
+        r.aout.to_primitive(),
+        r.amute
+    ]
+);
+
+register_proptest!(
+    port_a_control2,
+    PortAControl2Register,
+    1,
+    [bits(0, 2), bits(2, 2)],
+    |r| [r.adiv.to_primitive(), r.aclk.to_primitive()]
+);
+
+register_proptest!(
+    port_b_control1,
+    PortBControl1Register,
+    1,
+    [bits(0, 3), bit(3), bits(4, 2), bit(6)],
+    |r| [
+        r.afmt.to_primitive(),
+        r.am_slave,
+        r.aout.to_primitive(),
+        r.amute
+    ]
+);
+
+register_proptest!(
+    port_b_control2,
+    PortBControl2Register,
+    1,
+    [bits(0, 2), bits(2, 2)],
+    |r| [r.adiv.to_primitive(), r.aclk.to_primitive()]
+);
+
+register_proptest!(
+    transmitter_control1,
+    TransmitterControl1,
+    1,
+    [bit(0), bit(1), bit(2), bits(3, 2), bit(5), bits(6, 2)],
+    |r| [
+        r.bssl,
+        r.valid,
+        r.blsm,
+        r.txis.to_primitive(),
+        r.txclk.to_primitive(),
+        r.txdiv.to_primitive()
+    ]
+);
+
+register_proptest!(
+    transmitter_control2,
+    TransmitterControl2,
+    1,
+    [bit(0), bit(1), bit(2), bit(3), bit(4), bit(5), bits(6, 2)],
+    |r| [
+        r.txoff,
+        r.txmute,
+        r.aesoff,
+        r.txbtd,
+        r.ldmux,
+        r.aesmux,
+        r.bypmux.to_primitive()
+    ]
+);
+
+register_proptest!(
+    transmitter_control3,
+    TransmitterControl3,
+    1,
+    [bits(0, 2), bit(2)],
+    |r| [*r.txcus, r.valsel]
+);
+
+register_proptest!(
+    src_and_dit_status,
+    SrcAndDitStatus,
+    1,
+    [bit(1), bit(2), bit(3), bit(4)],
+    |r| [r.tbti, r.tslip, r.ready, r.ratio]
+);
+
+register_proptest!(
+    src_and_dit_interrupt_mask,
+    SrcAndDitInterruptMask,
+    1,
+    [bit(1), bit(2), bit(3), bit(4)],
+    |r| [r.mtbti, r.mtslip, r.mready, r.mratio]
+);
+
+register_proptest!(
+    src_and_dit_interrupt_mode,
+    SrcAndDitInterruptMode,
+    1,
+    [bits(0, 2), bits(2, 2), bits(4, 2), bits(6, 2)],
+    |r| [
+        r.tbtim.to_primitive(),
+        r.tslipm.to_primitive(),
+        r.readym.to_primitive(),
+        r.ratiom.to_primitive()
+    ]
+);
+
+register_proptest!(
+    receiver_control1,
+    ReceiverControl1,
+    1,
+    [bits(0, 2), bit(2), bits(3, 2), bit(5), bit(6)],
+    |r| [
+        r.rxmux.to_primitive(),
+        r.rxckoe,
+        r.rxckod.to_primitive(),
+        r.rxclk.to_primitive(),
+        r.rxbtd
+    ]
+);
+
+register_proptest!(
+    receiver_control2,
+    ReceiverControl2,
+    1,
+    [bit(0), bit(1)],
+    |r| [r.rxamll, r.lol]
+);
+
+register_proptest!(
+    receiver_pll_configuration,
+    ReceiverPllConfiguration,
+    3,
+    [bits(20, 4), bits(14, 6), bits(0, 14)],
+    |r| [*r.p, *r.j, *r.d]
+);
+
+register_proptest!(
+    non_pcm_audio_detection,
+    NonPcmAudioDetection,
+    1,
+    [bit(0), bit(1)],
+    |r| [r.iec61937, r.dts_cd]
+);
+
+register_proptest!(
+    receiver_status1,
+    ReceiverStatus1,
+    1,
+    [
+        bit(0),
+        bit(1),
+        bit(2),
+        bit(3),
+        bit(4),
+        bit(5),
+        bit(6),
+        bit(7)
+    ],
+    |r| [r.rbti, r.qchg, r.qcrc, r.cscrc, r.parity, r.vbit, r.bperr, r.unlock]
+);
+
+register_proptest!(
+    receiver_status2,
+    ReceiverStatus2,
+    1,
+    [bit(0), bit(1)],
+    |r| [r.oslip, r.cschg]
+);
+
+register_proptest!(
+    receiver_interrupt_mask1,
+    ReceiverInterruptMask1,
+    1,
+    [
+        bit(0),
+        bit(1),
+        bit(2),
+        bit(3),
+        bit(4),
+        bit(5),
+        bit(6),
+        bit(7)
+    ],
+    |r| [r.mrbti, r.mqchg, r.mqcrc, r.mcscrc, r.mparity, r.mvbit, r.mbperr, r.munlock]
+);
+
+register_proptest!(
+    receiver_interrupt_mask2,
+    ReceiverInterruptMask2,
+    1,
+    [bit(0), bit(1)],
+    |r| [r.moslip, r.mcschg]
+);
+
+register_proptest!(
+    receiver_interrupt_mode1,
+    ReceiverInterruptMode1,
+    1,
+    [bits(0, 2), bits(2, 2), bits(4, 2), bits(6, 2)],
+    |r| [
+        r.rbtim.to_primitive(),
+        r.qchgm.to_primitive(),
+        r.qcrcm.to_primitive(),
+        r.cscrcm.to_primitive()
+    ]
+);
+
+register_proptest!(
+    receiver_interrupt_mode2,
+    ReceiverInterruptMode2,
+    1,
+    [bits(0, 2), bits(2, 2), bits(4, 2), bits(6, 2)],
+    |r| [
+        r.paritym.to_primitive(),
+        r.vbitm.to_primitive(),
+        r.bperrm.to_primitive(),
+        r.unlockm.to_primitive()
+    ]
+);
+
+register_proptest!(
+    receiver_interrupt_mode3,
+    ReceiverInterruptMode3,
+    1,
+    [bits(0, 2), bits(2, 2)],
+    |r| [r.oslipm.to_primitive(), r.cschgm.to_primitive()]
+);
+
+register_proptest!(gpo, GpoRegister, 1, [bits(0, 4)], |r| [r
+    .function
+    .to_primitive()]);
+
+register_proptest!(
+    global_interrupt_status,
+    GlobalInterruptStatus,
+    1,
+    [bit(0), bit(1), bit(2)],
+    |r| [r.src, r.rx, r.tx]
+);
+
+register_proptest!(
+    src_control1,
+    SrcControl1,
+    1,
+    [bits(0, 2), bits(2, 2), bit(4), bit(6)],
+    |r| [
+        r.source.to_primitive(),
+        r.clock_source.to_primitive(),
+        r.mute,
+        r.track
+    ]
+);
+
+register_proptest!(
+    src_control2,
+    SrcControl2,
+    1,
+    [bits(0, 2), bit(2), bits(3, 2), bit(5)],
+    |r| [
+        r.interpolation_group_delay.to_primitive(),
+        r.dd_n,
+        r.deemphasis.to_primitive(),
+        r.autodem
+    ]
+);
+
+register_proptest!(src_control3, SrcControl3, 1, [bits(6, 2)], |r| [r
+    .output_word_length
+    .to_primitive()]);
+
+register_proptest!(src_control4, SrcControl4, 1, [bits(0, 8)], |r| [
+    r.attenuation
+]);
+
+register_proptest!(src_control5, SrcControl5, 1, [bits(0, 8)], |r| [
+    r.attenuation
+]);
+
+register_proptest!(src_ratio, SrcRatio, 2, [bits(11, 5), bits(0, 11)], |r| [
+    r.as_fixed().int().to_bits() >> 11,
+    r.as_fixed().frac().to_bits()
+]);
+
+proptest! {
+    #[test]
+    fn src_ratio_as_fixed(raw in any::<u16>()) {
+        let ratio = SrcRatio::unpack(&raw.to_be_bytes()).unwrap();
+        let fixed = ratio.as_fixed();
+        prop_assert_eq!(fixed.to_bits(), raw);
+        prop_assert_eq!(fixed.int().to_num::<u16>(), raw >> 11);
+        prop_assert_eq!(fixed.frac().to_bits(), raw & 0x7FF);
+        prop_assert_eq!(ratio.as_f32(), raw as f32 / 2048.0);
+    }
+}
+
+proptest! {
+    #[test]
+    fn decode_any_keeps_the_value(address in any::<u8>(), value in any::<u8>()) {
+        let decoded = decode_any(address, value);
+        prop_assert_eq!(decoded.address, address);
+        prop_assert_eq!(decoded.value, value);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2048))]
+
+    /// [`DeviceConfig::to_image`] packs by hand so it can be `const`. Unpacking its image with the
+    /// register types has to give the same config back.
+    #[test]
+    fn const_image_matches_register_types(image in any::<[u8; 21]>()) {
+        if let Ok(config) = DeviceConfig::from_image(&image) {
+            prop_assert_eq!(DeviceConfig::from_image(&config.to_image()), Ok(config));
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Tracking the SRC input rate from the measured input to output ratio.
+//!
+//! The SRC measures the ratio of its input rate to its output rate continuously and reports it
+//! in [`SrcRatio`] with 11 fractional bits. One step of the ratio is about 490 ppm at 1:1, so a
+//! single reading is coarse. [`RateMonitor`] smooths the readings exponentially in fixed point;
+//! as the reading dithers between neighbouring codes the average resolves the rate well below one
+//! step.
+//!
+//! Read the ratio on a schedule, every 100ms or so, and feed it to [`RateMonitor::update`]:
+//!
+//! ```
+//! # #[cfg(feature = "sim")]
+//! # fn main() {
+//! use src4392::clock::{Rate, SampleRate};
+//! use src4392::rate_monitor::RateMonitor;
+//! use src4392::sim::{Event, NoDelay, SimulatedSrc4392};
+//! use src4392::Src4392;
+//!
+//! let sim = SimulatedSrc4392::new();
+//! sim.apply_event(Event::Lock { rate_hz: 96_000 });
+//! let mut spi = &sim;
+//! let mut src4392 = Src4392::new(&sim, NoDelay);
+//!
+//! let mut monitor = RateMonitor::new(SampleRate::from_hz(48_000));
+//! let ratio = src4392.read_src_ratio(&mut spi).unwrap();
+//! let estimate = monitor.update(ratio.as_fixed()).unwrap();
+//! assert_eq!(estimate.nominal, Rate::Hz96000);
+//! assert_eq!(estimate.ppm, 0);
+//! # }
+//! # #[cfg(not(feature = "sim"))]
+//! # fn main() {}
+//! ```
+//!
+//! [`SrcRatio`]: crate::SrcRatio
+
+use crate::clock::{ClockError, ClockTree, Rate, SampleRate};
+use crate::config::PortConfig;
+use crate::sample_rate_converter::{ppm, ratio_input_hz, FixedU16, U11};
+
+/// Fractional bits kept by the filter beyond the 11 of the ratio register.
+const EXTRA_BITS: u32 = 8;
+/// The largest smoothing shift, which already averages over tens of thousands of readings.
+pub const MAX_SMOOTHING_SHIFT: u8 = 15;
+const FRACTION_BITS: u32 = 11 + EXTRA_BITS;
+
+/// What the monitor makes of the readings so far.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RateEstimate {
+    /// The smoothed input rate, rounded to whole Hz.
+    pub input: SampleRate,
+    /// The standard rate closest to [`RateEstimate::input`].
+    pub nominal: Rate,
+    /// How far the input is from [`RateEstimate::nominal`], in parts per million.
+    pub ppm: i32,
+    /// Whether the recent readings all stayed close to the average.
+    pub stable: bool,
+}
+
+/// Smooths SRC ratio readings into an input rate estimate.
+#[derive(Clone, Debug)]
+pub struct RateMonitor {
+    output: SampleRate,
+    smoothing_shift: u8,
+    stable_readings: u8,
+    tolerance_steps: u16,
+    /// The smoothed ratio with [`FRACTION_BITS`] fractional bits.
+    average: Option<u32>,
+    /// How many readings in a row were within the tolerance of the average.
+    stable_run: u8,
+}
+
+impl RateMonitor {
+    /// A monitor for an SRC running at `output`. Each reading moves the average by 1/8 of its
+    /// difference, and the rate is stable once 8 readings in a row are within 2 steps of the
+    /// ratio register of the average.
+    pub const fn new(output: SampleRate) -> Self {
+        RateMonitor {
+            output,
+            smoothing_shift: 3,
+            stable_readings: 8,
+            tolerance_steps: 2,
+            average: None,
+            stable_run: 0,
+        }
+    }
+
+    /// A monitor for an SRC whose output is the master port `port`, clocked from `clocks`.
+    pub fn for_port(clocks: &ClockTree, port: &PortConfig) -> Result<Self, ClockError> {
+        clocks
+            .port_clocks(port)
+            .map(|port_clocks| RateMonitor::new(port_clocks.lrck))
+    }
+
+    /// Each reading moves the average by `1 / 2^shift` of its difference from the average.
+    ///
+    /// # Panics
+    ///
+    /// When `shift` is more than [`MAX_SMOOTHING_SHIFT`].
+    pub const fn with_smoothing(mut self, shift: u8) -> Self {
+        assert!(shift <= MAX_SMOOTHING_SHIFT, "smoothing shift out of range");
+        self.smoothing_shift = shift;
+        self
+    }
+
+    /// The rate is stable once `readings` readings in a row are within `tolerance_steps` steps
+    /// of the ratio register of the average. The first reading only sets the average, so the
+    /// earliest a rate can be stable is the reading after it.
+    ///
+    /// # Panics
+    ///
+    /// When `readings` is 0.
+    pub const fn with_stability(mut self, readings: u8, tolerance_steps: u16) -> Self {
+        assert!(readings > 0, "stability needs at least one reading");
+        self.stable_readings = readings;
+        self.tolerance_steps = tolerance_steps;
+        self
+    }
+
+    pub const fn output(&self) -> SampleRate {
+        self.output
+    }
+
+    /// Forgets the readings so far, for example after the input switched.
+    pub fn reset(&mut self) {
+        self.average = None;
+        self.stable_run = 0;
+    }
+
+    /// Adds a ratio reading. A zero ratio means the SRC has no input; the monitor is reset and
+    /// `None` returned.
+    pub fn update(&mut self, ratio: FixedU16<U11>) -> Option<RateEstimate> {
+        if ratio == FixedU16::<U11>::ZERO {
+            self.reset();
+            return None;
+        }
+        let reading = u32::from(ratio.to_bits()) << EXTRA_BITS;
+        let average = match self.average {
+            None => reading,
+            Some(average) => {
+                let tolerance = u32::from(self.tolerance_steps) << EXTRA_BITS;
+                if reading.abs_diff(average) <= tolerance {
+                    self.stable_run = self.stable_run.saturating_add(1);
+                } else {
+                    self.stable_run = 0;
+                }
+                let step = (i64::from(reading) - i64::from(average)) >> self.smoothing_shift;
+                (i64::from(average) + step) as u32
+            }
+        };
+        self.average = Some(average);
+        self.estimate()
+    }
+
+    /// The estimate from the readings so far, without adding one.
+    pub fn estimate(&self) -> Option<RateEstimate> {
+        let average = u64::from(self.average?);
+        let input_hz = ratio_input_hz(average, FRACTION_BITS, self.output.hz())?;
+        let input = SampleRate::from_hz(input_hz);
+        let nominal = Rate::nearest(input);
+        // Compared unrounded, against the nominal rate over the output rate.
+        let scaled = average * u64::from(self.output.hz());
+        let nominal_scaled = u64::from(nominal.hz()) << FRACTION_BITS;
+        Some(RateEstimate {
+            input,
+            nominal,
+            ppm: ppm(scaled, nominal_scaled)?,
+            stable: self.stable_run >= self.stable_readings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(bits: u16) -> FixedU16<U11> {
+        FixedU16::<U11>::from_bits(bits)
+    }
+
+    #[test]
+    fn exact_ratio() {
+        let mut monitor = RateMonitor::new(SampleRate::from_hz(48_000));
+        let estimate = monitor.update(ratio(4096)).unwrap();
+        assert_eq!(estimate.input, SampleRate::from_hz(96_000));
+        assert_eq!(estimate.nominal, Rate::Hz96000);
+        assert_eq!(estimate.ppm, 0);
+        assert!(!estimate.stable);
+    }
+
+    #[test]
+    fn dithering_readings_resolve_below_one_step() {
+        // 44.1 kHz into 48 kHz is 1881.6 steps; the reading dithers between 1881 and 1882.
+        let mut monitor = RateMonitor::new(SampleRate::from_hz(48_000)).with_smoothing(4);
+        let mut estimate = None;
+        for reading in [1881, 1882, 1882, 1881, 1882].iter().cycle().take(200) {
+            estimate = monitor.update(ratio(*reading));
+        }
+        let estimate = estimate.unwrap();
+        assert_eq!(estimate.nominal, Rate::Hz44100);
+        assert!(estimate.ppm.abs() < 100, "{} ppm", estimate.ppm);
+        assert!(estimate.stable);
+    }
+
+    #[test]
+    fn offset_in_ppm() {
+        // A 48 kHz word clock running 1% fast, measured against 48 kHz.
+        let mut monitor = RateMonitor::new(SampleRate::from_hz(48_000));
+        let estimate = monitor.update(ratio(2068)).unwrap();
+        assert_eq!(estimate.nominal, Rate::Hz48000);
+        assert_eq!(estimate.ppm, 9765);
+    }
+
+    #[test]
+    fn jumps_break_stability() {
+        let mut monitor = RateMonitor::new(SampleRate::from_hz(48_000)).with_stability(3, 1);
+        for _ in 0..4 {
+            monitor.update(ratio(2048));
+        }
+        assert!(monitor.estimate().unwrap().stable);
+        assert!(!monitor.update(ratio(2100)).unwrap().stable);
+        assert_eq!(monitor.update(ratio(0)), None);
+        assert_eq!(monitor.estimate(), None);
+    }
+
+    #[test]
+    fn slowest_smoothing_still_moves() {
+        let mut monitor =
+            RateMonitor::new(SampleRate::from_hz(48_000)).with_smoothing(MAX_SMOOTHING_SHIFT);
+        monitor.update(ratio(2048));
+        monitor.update(ratio(4096));
+        assert!(monitor.average.unwrap() > 2048 << EXTRA_BITS);
+    }
+
+    #[test]
+    #[should_panic(expected = "smoothing shift out of range")]
+    fn smoothing_out_of_range() {
+        let _ = RateMonitor::new(SampleRate::from_hz(48_000)).with_smoothing(64);
+    }
+
+    #[test]
+    #[should_panic(expected = "stability needs at least one reading")]
+    fn stability_needs_a_reading() {
+        let _ = RateMonitor::new(SampleRate::from_hz(48_000)).with_stability(0, 1);
+    }
+}
@@ -0,0 +1,315 @@
+use packed_struct::prelude::*;
+
+use crate::interrupt::InterruptMode;
+use crate::{registers::Registers, RegisterAddress};
+
+#[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReceiverInput {
+    #[default]
+    Rx1 = 0b00,
+    Rx2 = 0b01,
+    Rx3 = 0b10,
+    Rx4 = 0b11,
+}
+
+/// Divider applied to the recovered master clock before it is output on RXCKO.
+#[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecoveredClockDivider {
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "1"))]
+    _1 = 0b00,
+    #[cfg_attr(feature = "serde", serde(rename = "2"))]
+    _2 = 0b01,
+    #[cfg_attr(feature = "serde", serde(rename = "4"))]
+    _4 = 0b10,
+    #[cfg_attr(feature = "serde", serde(rename = "8"))]
+    _8 = 0b11,
+}
+
+/// The reference clock used by the DIR PLL.
+#[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReceiverReferenceClock {
+    #[default]
+    Rxcki = 0b0,
+    Mclk = 0b1,
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct ReceiverControl1 {
+    #[packed_field(bits = "0..2", ty = "enum")]
+    pub rxmux: ReceiverInput,
+    /// Enables the recovered master clock output on RXCKO.
+    #[packed_field(bits = "2")]
+    pub rxckoe: bool,
+    #[packed_field(bits = "3..5", ty = "enum")]
+    pub rxckod: RecoveredClockDivider,
+    #[packed_field(bits = "5", ty = "enum")]
+    pub rxclk: ReceiverReferenceClock,
+    #[packed_field(bits = "6")]
+    pub rxbtd: bool,
+    #[packed_field(bits = "7")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZero<packed_bits::Bits<1>>,
+}
+impl ReceiverControl1 {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverControl;
+}
+impl RegisterAddress<Registers> for ReceiverControl1 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct ReceiverControl2 {
+    /// Automatically mutes the DIR output when the PLL loses lock.
+    #[packed_field(bits = "0")]
+    pub rxamll: bool,
+    /// false: the DIR output is held on loss of lock, true: it is zeroed.
+    #[packed_field(bits = "1")]
+    pub lol: bool,
+    #[packed_field(bits = "2..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZeroes<packed_bits::Bits<6>>,
+}
+impl ReceiverControl2 {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverControl2;
+}
+impl RegisterAddress<Registers> for ReceiverControl2 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+/// The P, J and D values of the DIR PLL, spread across the three PLL configuration registers.
+///
+/// The PLL output frequency is `reference * (J.D) / P`, where D is the four decimal digit
+/// fractional part of the multiplier.
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "3")]
+pub struct ReceiverPllConfiguration {
+    #[packed_field(bits = "20..24")]
+    pub p: Integer<u8, packed_bits::Bits<4>>,
+    #[packed_field(bits = "14..20")]
+    pub j: Integer<u8, packed_bits::Bits<6>>,
+    #[packed_field(bits = "0..14")]
+    pub d: Integer<u16, packed_bits::Bits<14>>,
+}
+impl ReceiverPllConfiguration {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverPllConfiguration;
+}
+impl RegisterAddress<Registers> for ReceiverPllConfiguration {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct ReceiverStatus1 {
+    /// DIR block start.
+    #[packed_field(bits = "0")]
+    pub rbti: bool,
+    /// The Q channel sub-code changed.
+    #[packed_field(bits = "1")]
+    pub qchg: bool,
+    /// Q channel sub-code CRC error.
+    #[packed_field(bits = "2")]
+    pub qcrc: bool,
+    /// Channel status CRC error.
+    #[packed_field(bits = "3")]
+    pub cscrc: bool,
+    #[packed_field(bits = "4")]
+    pub parity: bool,
+    /// The validity bit of the received sub frame is set.
+    #[packed_field(bits = "5")]
+    pub vbit: bool,
+    /// Bi-phase encoding error.
+    #[packed_field(bits = "6")]
+    pub bperr: bool,
+    /// The DIR PLL isn't locked to the input.
+    #[packed_field(bits = "7")]
+    pub unlock: bool,
+}
+impl ReceiverStatus1 {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverStatus;
+}
+impl RegisterAddress<Registers> for ReceiverStatus1 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct ReceiverStatus2 {
+    /// The DIR output slipped against the output clock.
+    #[packed_field(bits = "0")]
+    pub oslip: bool,
+    /// The received channel status changed.
+    #[packed_field(bits = "1")]
+    pub cschg: bool,
+    #[packed_field(bits = "2..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZeroes<packed_bits::Bits<6>>,
+}
+impl ReceiverStatus2 {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverStatus2;
+}
+impl RegisterAddress<Registers> for ReceiverStatus2 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+/// true enables the interrupt for the matching [`ReceiverStatus1`] bit.
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct ReceiverInterruptMask1 {
+    #[packed_field(bits = "0")]
+    pub mrbti: bool,
+    #[packed_field(bits = "1")]
+    pub mqchg: bool,
+    #[packed_field(bits = "2")]
+    pub mqcrc: bool,
+    #[packed_field(bits = "3")]
+    pub mcscrc: bool,
+    #[packed_field(bits = "4")]
+    pub mparity: bool,
+    #[packed_field(bits = "5")]
+    pub mvbit: bool,
+    #[packed_field(bits = "6")]
+    pub mbperr: bool,
+    #[packed_field(bits = "7")]
+    pub munlock: bool,
+}
+impl ReceiverInterruptMask1 {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverInterruptMask;
+}
+impl RegisterAddress<Registers> for ReceiverInterruptMask1 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+/// true enables the interrupt for the matching [`ReceiverStatus2`] bit.
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct ReceiverInterruptMask2 {
+    #[packed_field(bits = "0")]
+    pub moslip: bool,
+    #[packed_field(bits = "1")]
+    pub mcschg: bool,
+    #[packed_field(bits = "2..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZeroes<packed_bits::Bits<6>>,
+}
+impl ReceiverInterruptMask2 {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverInterruptMask2;
+}
+impl RegisterAddress<Registers> for ReceiverInterruptMask2 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct ReceiverInterruptMode1 {
+    #[packed_field(bits = "0..2", ty = "enum")]
+    pub rbtim: InterruptMode,
+    #[packed_field(bits = "2..4", ty = "enum")]
+    pub qchgm: InterruptMode,
+    #[packed_field(bits = "4..6", ty = "enum")]
+    pub qcrcm: InterruptMode,
+    #[packed_field(bits = "6..8", ty = "enum")]
+    pub cscrcm: InterruptMode,
+}
+impl ReceiverInterruptMode1 {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverInterruptMode;
+}
+impl RegisterAddress<Registers> for ReceiverInterruptMode1 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct ReceiverInterruptMode2 {
+    #[packed_field(bits = "0..2", ty = "enum")]
+    pub paritym: InterruptMode,
+    #[packed_field(bits = "2..4", ty = "enum")]
+    pub vbitm: InterruptMode,
+    #[packed_field(bits = "4..6", ty = "enum")]
+    pub bperrm: InterruptMode,
+    #[packed_field(bits = "6..8", ty = "enum")]
+    pub unlockm: InterruptMode,
+}
+impl ReceiverInterruptMode2 {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverInterruptMode2;
+}
+impl RegisterAddress<Registers> for ReceiverInterruptMode2 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct ReceiverInterruptMode3 {
+    #[packed_field(bits = "0..2", ty = "enum")]
+    pub oslipm: InterruptMode,
+    #[packed_field(bits = "2..4", ty = "enum")]
+    pub cschgm: InterruptMode,
+    #[packed_field(bits = "4..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZeroes<packed_bits::Bits<4>>,
+}
+impl ReceiverInterruptMode3 {
+    pub const REGISTER_ADDRESS: Registers = Registers::ReceiverInterruptMode3;
+}
+impl RegisterAddress<Registers> for ReceiverInterruptMode3 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct NonPcmAudioDetection {
+    /// An IEC 61937 data burst preamble was detected. The burst info and length are held in the
+    /// Pc and Pd preamble registers.
+    #[packed_field(bits = "0")]
+    pub iec61937: bool,
+    /// A DTS CD/LD stream was detected.
+    #[packed_field(bits = "1")]
+    pub dts_cd: bool,
+    #[packed_field(bits = "2..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZeroes<packed_bits::Bits<6>>,
+}
+impl NonPcmAudioDetection {
+    pub const REGISTER_ADDRESS: Registers = Registers::NonPcmAudioDetection;
+}
+impl RegisterAddress<Registers> for NonPcmAudioDetection {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
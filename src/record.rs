@@ -0,0 +1,511 @@
+//! Recording of the SPI traffic between the driver and the device, to prove a change to the driver
+//! didn't change what goes over the bus.
+//!
+//! A [`Recorder`] hands out a [`RecordingPin`] wrapping the chip select and a [`RecordingSpi`]
+//! wrapping the bus. Every byte between the chip select going low and going high again is
+//! collected into one [`Transaction`]. Recordings can be rendered as a plain golden trace, one
+//! transaction per line:
+//!
+//! ```text
+//! # comments and blank lines are ignored
+//! R 03 00
+//! W 03 19
+//! ```
+//!
+//! or decoded into register fields for reading. Golden traces don't hold timestamps so
+//! they stay stable between runs.
+//!
+//! ```
+//! # #[cfg(feature = "sim")]
+//! # fn main() {
+//! use src4392::record::Recorder;
+//! use src4392::sim::{NoDelay, SimulatedSrc4392};
+//! use src4392::Src4392;
+//!
+//! let sim = SimulatedSrc4392::new();
+//! let recorder = Recorder::new();
+//! let mut spi = recorder.spi(&sim);
+//! let mut src4392 = Src4392::new(recorder.pin(&sim), NoDelay);
+//! src4392.reset(&mut spi).unwrap();
+//! recorder.compare("W 01 80").unwrap();
+//! # }
+//! # #[cfg(not(feature = "sim"))]
+//! # fn main() {}
+//! ```
+
+use core::cell::RefCell;
+use core::fmt::{self, Write as _};
+
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use embedded_hal::blocking::spi::Transfer;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::decode::decode_any;
+use crate::registers::{Page, Registers};
+use crate::ReadWrite;
+
+/// A source of timestamps for recorded transactions.
+pub trait Clock {
+    fn now_us(&mut self) -> u64;
+}
+
+/// Timestamps relative to the instant.
+impl Clock for std::time::Instant {
+    fn now_us(&mut self) -> u64 {
+        self.elapsed().as_micros() as u64
+    }
+}
+
+/// The clock of a [`Recorder`] made without one. It is never read.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NoClock;
+
+impl Clock for NoClock {
+    fn now_us(&mut self) -> u64 {
+        0
+    }
+}
+
+/// One chip select framed SPI transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    /// When the chip select was asserted, if the recorder has a clock.
+    pub timestamp_us: Option<u64>,
+    /// The page selected when the transaction started.
+    pub page: u8,
+    /// The start address, without the read bit.
+    pub address: u8,
+    pub direction: ReadWrite,
+    /// The bytes written to, or read from, the registers starting at `address`. The command and
+    /// dummy bytes aren't included.
+    pub data: Vec<u8>,
+}
+
+impl Transaction {
+    fn from_frame(timestamp_us: Option<u64>, page: u8, mosi: &[u8], miso: &[u8]) -> Self {
+        let command = mosi.first().copied().unwrap_or(0);
+        let direction = if command & 0b1000_0000 != 0 {
+            ReadWrite::Read
+        } else {
+            ReadWrite::Write
+        };
+        let data = match direction {
+            ReadWrite::Read => miso.get(2..),
+            ReadWrite::Write => mosi.get(2..),
+        };
+        Self {
+            timestamp_us,
+            page,
+            address: command & 0b0111_1111,
+            direction,
+            data: data.unwrap_or_default().to_vec(),
+        }
+    }
+
+    /// The register at `address`, if the transaction is on the control page.
+    pub fn register(&self) -> Option<Registers> {
+        if self.page == Page::Control as u8 {
+            Registers::from_address(self.address)
+        } else {
+            None
+        }
+    }
+
+    fn direction_char(&self) -> char {
+        match self.direction {
+            ReadWrite::Read => 'R',
+            ReadWrite::Write => 'W',
+        }
+    }
+}
+
+/// Renders the transaction as one line of a golden trace.
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:02X}", self.direction_char(), self.address)?;
+        for byte in &self.data {
+            write!(f, " {:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// The first line at which a recording and a golden trace differ. A `None` side ran out of lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceMismatch {
+    /// The line number in the golden trace, starting from 1.
+    pub line: usize,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for TraceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "trace differs at line {}: expected `{}`, recorded `{}`",
+            self.line,
+            self.expected.as_deref().unwrap_or("<end of trace>"),
+            self.actual.as_deref().unwrap_or("<end of recording>"),
+        )
+    }
+}
+
+struct State<C> {
+    clock: Option<C>,
+    transactions: Vec<Transaction>,
+    page: u8,
+    framing: bool,
+    timestamp_us: Option<u64>,
+    mosi: Vec<u8>,
+    miso: Vec<u8>,
+}
+
+impl<C: Clock> State<C> {
+    fn new(clock: Option<C>) -> Self {
+        Self {
+            clock,
+            transactions: Vec::new(),
+            page: Page::Control as u8,
+            framing: false,
+            timestamp_us: None,
+            mosi: Vec::new(),
+            miso: Vec::new(),
+        }
+    }
+
+    fn begin(&mut self) {
+        self.framing = true;
+        self.timestamp_us = self.clock.as_mut().map(Clock::now_us);
+        self.mosi.clear();
+        self.miso.clear();
+    }
+
+    fn end(&mut self) {
+        if !self.framing {
+            return;
+        }
+        self.framing = false;
+        let transaction =
+            Transaction::from_frame(self.timestamp_us, self.page, &self.mosi, &self.miso);
+        if transaction.direction == ReadWrite::Write
+            && transaction.address == Registers::PageSelection as u8
+        {
+            if let Some(page) = transaction.data.first() {
+                self.page = page & Registers::PageSelection.writable_bits();
+            }
+        }
+        self.transactions.push(transaction);
+    }
+}
+
+/// Collects the transactions made through its [`RecordingPin`] and [`RecordingSpi`].
+pub struct Recorder<C = NoClock> {
+    state: RefCell<State<C>>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recorder {
+    /// A recorder without timestamps.
+    pub fn new() -> Self {
+        Self {
+            state: RefCell::new(State::new(None)),
+        }
+    }
+}
+
+impl<C: Clock> Recorder<C> {
+    /// A recorder stamping every transaction with the time from `clock`.
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            state: RefCell::new(State::new(Some(clock))),
+        }
+    }
+
+    /// Wraps the chip select pin. Pass the result to [`crate::Src4392::new`].
+    pub fn pin<P: OutputPin>(&self, pin: P) -> RecordingPin<'_, P, C> {
+        RecordingPin {
+            recorder: self,
+            pin,
+        }
+    }
+
+    /// Wraps the SPI bus. Pass the result to every driver call.
+    pub fn spi<SPI: Transfer<u8>>(&self, spi: SPI) -> RecordingSpi<'_, SPI, C> {
+        RecordingSpi {
+            recorder: self,
+            spi,
+        }
+    }
+
+    /// The transactions recorded so far.
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.state.borrow().transactions.clone()
+    }
+
+    /// Forgets the transactions recorded so far. The selected page is kept.
+    pub fn clear(&self) {
+        self.state.borrow_mut().transactions.clear();
+    }
+
+    /// The recording as a golden trace.
+    pub fn trace(&self) -> String {
+        let mut trace = String::new();
+        for transaction in &self.state.borrow().transactions {
+            writeln!(trace, "{}", transaction).unwrap();
+        }
+        trace
+    }
+
+    /// The recording with one line per register byte, decoded with [`decode_any`] on the control
+    /// page.
+    pub fn decoded(&self) -> String {
+        let mut out = String::new();
+        for transaction in &self.state.borrow().transactions {
+            if let Some(timestamp_us) = transaction.timestamp_us {
+                write!(out, "[{:>10}us] ", timestamp_us).unwrap();
+            }
+            write!(out, "{}", transaction.direction_char()).unwrap();
+            if transaction.page != Page::Control as u8 {
+                write!(out, " page {}", transaction.page).unwrap();
+            }
+            if transaction.data.is_empty() {
+                writeln!(out, " 0x{:02X} (no data)", transaction.address).unwrap();
+            }
+            for (offset, byte) in transaction.data.iter().enumerate() {
+                let address = transaction.address.wrapping_add(offset as u8);
+                if transaction.page == Page::Control as u8 {
+                    writeln!(out, " {}", decode_any(address, *byte)).unwrap();
+                } else {
+                    writeln!(out, " 0x{:02X}=0x{:02X}", address, byte).unwrap();
+                }
+            }
+        }
+        out
+    }
+
+    /// Compares the recording with a golden trace, ignoring comments, blank lines, case and
+    /// spacing.
+    pub fn compare(&self, golden: &str) -> Result<(), TraceMismatch> {
+        let mut expected = golden
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        let state = self.state.borrow();
+        let mut actual = state.transactions.iter().map(|t| t.to_string());
+        let mut last_line = golden.lines().count();
+        loop {
+            match (expected.next(), actual.next()) {
+                (None, None) => return Ok(()),
+                (Some((line, expected)), actual) => {
+                    let normalised = expected
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .to_uppercase();
+                    if actual.as_ref() != Some(&normalised) {
+                        return Err(TraceMismatch {
+                            line,
+                            expected: Some(expected.into()),
+                            actual,
+                        });
+                    }
+                    last_line = line;
+                }
+                (None, actual) => {
+                    return Err(TraceMismatch {
+                        line: last_line + 1,
+                        expected: None,
+                        actual,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Compares the recording with the golden trace at `path`, panicking with the decoded
+    /// recording if they differ.
+    ///
+    /// With the `SRC4392_BLESS` environment variable set the file is overwritten with the
+    /// recording instead.
+    pub fn assert_golden(&self, path: impl AsRef<std::path::Path>) {
+        let path = path.as_ref();
+        if std::env::var_os("SRC4392_BLESS").is_some() {
+            std::fs::write(path, self.trace())
+                .unwrap_or_else(|e| panic!("writing {}: {}", path.display(), e));
+            return;
+        }
+        let golden = std::fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+        if let Err(mismatch) = self.compare(&golden) {
+            panic!(
+                "{}: {}\nrecorded:\n{}",
+                path.display(),
+                mismatch,
+                self.decoded()
+            );
+        }
+    }
+}
+
+/// A chip select pin whose edges frame recorded transactions.
+pub struct RecordingPin<'a, P, C = NoClock> {
+    recorder: &'a Recorder<C>,
+    pin: P,
+}
+
+impl<P: OutputPin, C: Clock> OutputPin for RecordingPin<'_, P, C> {
+    type Error = P::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.recorder.state.borrow_mut().begin();
+        self.pin.set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.recorder.state.borrow_mut().end();
+        self.pin.set_high()
+    }
+}
+
+/// An SPI bus whose traffic is added to the current transaction.
+pub struct RecordingSpi<'a, SPI, C = NoClock> {
+    recorder: &'a Recorder<C>,
+    spi: SPI,
+}
+
+impl<SPI: Transfer<u8>, C: Clock> Transfer<u8> for RecordingSpi<'_, SPI, C> {
+    type Error = SPI::Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        self.recorder
+            .state
+            .borrow_mut()
+            .mosi
+            .extend_from_slice(words);
+        let result = self.spi.transfer(words)?;
+        self.recorder
+            .state
+            .borrow_mut()
+            .miso
+            .extend_from_slice(result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump::FULL_DUMP_LEN;
+    use crate::port_control::{
+        AudioFormat, OutputDataSource, PortClockSource, PortMasterClockDivider,
+    };
+    use crate::sample_rate_converter::{
+        Deemphasis, InterpolationFilterGroupDelay, SrcClockSource, SrcSource,
+    };
+    use crate::sim::{NoDelay, SimulatedSrc4392};
+    use crate::{DeviceConfig, Port, PowerConfig, Src4392, SrcConfig};
+    use std::format;
+
+    fn golden(name: &str) -> String {
+        format!("{}/tests/golden/{}.trace", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn configure_port_order() {
+        let sim = SimulatedSrc4392::new();
+        let recorder = Recorder::new();
+        let mut spi = recorder.spi(&sim);
+        let mut src4392 = Src4392::new(recorder.pin(&sim), NoDelay);
+        src4392
+            .configure_port(
+                &mut spi,
+                Port::B,
+                AudioFormat::I2S,
+                OutputDataSource::SRC,
+                PortMasterClockDivider::_256,
+                PortClockSource::Mclk,
+                true,
+            )
+            .unwrap();
+        recorder.assert_golden(golden("configure_port"));
+    }
+
+    #[test]
+    fn set_src_order() {
+        let sim = SimulatedSrc4392::new();
+        let recorder = Recorder::new();
+        let mut spi = recorder.spi(&sim);
+        let mut src4392 = Src4392::new(recorder.pin(&sim), NoDelay);
+        src4392
+            .set_src(
+                &mut spi,
+                SrcSource::DIR,
+                SrcClockSource::Mclk,
+                InterpolationFilterGroupDelay::_32,
+                Deemphasis::None,
+                false,
+            )
+            .unwrap();
+        recorder.assert_golden(golden("set_src"));
+    }
+
+    #[test]
+    fn load_image_bursts() {
+        let sim = SimulatedSrc4392::new();
+        let recorder = Recorder::new();
+        let mut spi = recorder.spi(&sim);
+        let mut src4392 = Src4392::new(recorder.pin(&sim), NoDelay);
+        let config = DeviceConfig::builder()
+            .power(PowerConfig::ALL_ON)
+            .src(SrcConfig {
+                source: SrcSource::DIR,
+                ..SrcConfig::RESET
+            })
+            .build()
+            .unwrap();
+        src4392.load_image(&mut spi, &config.to_image()).unwrap();
+        recorder.assert_golden(golden("load_image"));
+    }
+
+    #[test]
+    fn mismatch_and_decoding() {
+        let sim = SimulatedSrc4392::new();
+        let recorder = Recorder::with_clock(std::time::Instant::now());
+        let mut spi = recorder.spi(&sim);
+        let mut src4392 = Src4392::new(recorder.pin(&sim), NoDelay);
+        src4392.dump(&mut spi, &mut [0u8; FULL_DUMP_LEN]).unwrap();
+        src4392.reset(&mut spi).unwrap();
+
+        let transactions = recorder.transactions();
+        assert!(transactions.iter().all(|t| t.timestamp_us.is_some()));
+        assert_eq!(transactions[3].page, Page::ReceiverBuffers as u8);
+        assert_eq!(transactions[3].register(), None);
+        assert_eq!(transactions[3].data.len(), 96);
+
+        let decoded = recorder.decoded();
+        assert!(decoded.contains("PageSelection(0x7F)=0x01: PAGE=1"));
+        assert!(decoded.contains("page 1 0x00=0x00"));
+
+        let mut golden = recorder.trace();
+        golden.push_str("W 01 00\n");
+        assert_eq!(
+            recorder.compare(&golden),
+            Err(TraceMismatch {
+                line: transactions.len() + 1,
+                expected: Some("W 01 00".into()),
+                actual: None,
+            })
+        );
+        recorder.clear();
+        assert_eq!(recorder.compare("# nothing\n\n"), Ok(()));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{AsAddressByte, ReadWrite};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Registers {
     Reset = 0x01,
     GlobalInterruptStatus = 0x02,
@@ -56,6 +57,153 @@ pub enum Registers {
     PageSelection = 0x7F,
 }
 
+impl Registers {
+    /// Every register on page 0, in address order. [`Registers::PageSelection`] is left out as it
+    /// is present on every page.
+    pub const ALL: [Registers; 51] = [
+        Registers::Reset,
+        Registers::GlobalInterruptStatus,
+        Registers::PortAControl,
+        Registers::PortAControl2,
+        Registers::PortBControl,
+        Registers::PortBControl2,
+        Registers::TransmitterControl,
+        Registers::TransmitterControl2,
+        Registers::TransmitterControl3,
+        Registers::SrcAndDitStatus,
+        Registers::SrcAndDitInterruptMask,
+        Registers::SrcAndDitInterruptMode,
+        Registers::ReceiverControl,
+        Registers::ReceiverControl2,
+        Registers::ReceiverPllConfiguration,
+        Registers::ReceiverPllConfiguration2,
+        Registers::ReceiverPllConfiguration3,
+        Registers::NonPcmAudioDetection,
+        Registers::ReceiverStatus,
+        Registers::ReceiverStatus2,
+        Registers::ReceiverStatus3,
+        Registers::ReceiverInterruptMask,
+        Registers::ReceiverInterruptMask2,
+        Registers::ReceiverInterruptMode,
+        Registers::ReceiverInterruptMode2,
+        Registers::ReceiverInterruptMode3,
+        Registers::Gpo1,
+        Registers::Gpo2,
+        Registers::Gpo3,
+        Registers::Gpo4,
+        Registers::AudioCdQChannelSubCode1,
+        Registers::AudioCdQChannelSubCode2,
+        Registers::AudioCdQChannelSubCode3,
+        Registers::AudioCdQChannelSubCode4,
+        Registers::AudioCdQChannelSubCode5,
+        Registers::AudioCdQChannelSubCode6,
+        Registers::AudioCdQChannelSubCode7,
+        Registers::AudioCdQChannelSubCode8,
+        Registers::AudioCdQChannelSubCode9,
+        Registers::AudioCdQChannelSubCode10,
+        Registers::PcBurstPreambleHighByte,
+        Registers::PcBurstPreambleLowByte,
+        Registers::PdBurstPreambleHighByte,
+        Registers::PdBurstPreambleLowByte,
+        Registers::SrcControl,
+        Registers::SrcControl2,
+        Registers::SrcControl3,
+        Registers::SrcControl4,
+        Registers::SrcControl5,
+        Registers::SrcInputOutputRatio1,
+        Registers::SrcInputOutputRatio2,
+    ];
+
+    pub fn from_address(address: u8) -> Option<Registers> {
+        if address == Registers::PageSelection as u8 {
+            return Some(Registers::PageSelection);
+        }
+        Registers::ALL.iter().copied().find(|r| *r as u8 == address)
+    }
+
+    /// The bits of the register that can be written. Reserved and status bits read back as zero
+    /// or reflect device state whatever is written to them.
+    pub fn writable_bits(&self) -> u8 {
+        match self {
+            Registers::Reset => 0b1011_1111,
+            Registers::PortAControl | Registers::PortBControl => 0b0111_1111,
+            Registers::PortAControl2 | Registers::PortBControl2 => 0b0000_1111,
+            Registers::TransmitterControl | Registers::TransmitterControl2 => 0b1111_1111,
+            Registers::TransmitterControl3 => 0b0000_0111,
+            Registers::SrcAndDitInterruptMask => 0b0001_1110,
+            Registers::SrcAndDitInterruptMode => 0b1111_1111,
+            Registers::ReceiverControl => 0b0111_1111,
+            Registers::ReceiverControl2 => 0b0000_0011,
+            Registers::ReceiverPllConfiguration
+            | Registers::ReceiverPllConfiguration2
+            | Registers::ReceiverPllConfiguration3 => 0b1111_1111,
+            Registers::ReceiverInterruptMask
+            | Registers::ReceiverInterruptMode
+            | Registers::ReceiverInterruptMode2 => 0b1111_1111,
+            Registers::ReceiverInterruptMask2 => 0b0000_0011,
+            Registers::ReceiverInterruptMode3 => 0b0000_1111,
+            Registers::Gpo1 | Registers::Gpo2 | Registers::Gpo3 | Registers::Gpo4 => 0b0000_1111,
+            Registers::SrcControl => 0b0101_1111,
+            Registers::SrcControl2 => 0b0011_1111,
+            Registers::SrcControl3 => 0b1100_0000,
+            Registers::SrcControl4 | Registers::SrcControl5 => 0b1111_1111,
+            Registers::PageSelection => 0b0000_0011,
+            Registers::GlobalInterruptStatus
+            | Registers::SrcAndDitStatus
+            | Registers::NonPcmAudioDetection
+            | Registers::ReceiverStatus
+            | Registers::ReceiverStatus2
+            | Registers::ReceiverStatus3
+            | Registers::AudioCdQChannelSubCode1
+            | Registers::AudioCdQChannelSubCode2
+            | Registers::AudioCdQChannelSubCode3
+            | Registers::AudioCdQChannelSubCode4
+            | Registers::AudioCdQChannelSubCode5
+            | Registers::AudioCdQChannelSubCode6
+            | Registers::AudioCdQChannelSubCode7
+            | Registers::AudioCdQChannelSubCode8
+            | Registers::AudioCdQChannelSubCode9
+            | Registers::AudioCdQChannelSubCode10
+            | Registers::PcBurstPreambleHighByte
+            | Registers::PcBurstPreambleLowByte
+            | Registers::PdBurstPreambleHighByte
+            | Registers::PdBurstPreambleLowByte
+            | Registers::SrcInputOutputRatio1
+            | Registers::SrcInputOutputRatio2 => 0,
+        }
+    }
+
+    /// The writable bits of the register after a reset, from the datasheet register map. `None`
+    /// where no reset value is relied on: read only registers, which reflect device state, and
+    /// the receiver PLL registers, which are always written before the DIR is used.
+    pub fn reset_value(&self) -> Option<u8> {
+        match self {
+            Registers::ReceiverPllConfiguration
+            | Registers::ReceiverPllConfiguration2
+            | Registers::ReceiverPllConfiguration3 => None,
+            register if register.is_writable() => Some(0x00),
+            _ => None,
+        }
+    }
+
+    /// false for status registers and other read only addresses.
+    pub fn is_writable(&self) -> bool {
+        self.writable_bits() != 0
+    }
+}
+
+/// The register pages selected through [`Registers::PageSelection`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Page {
+    /// The control and status registers.
+    Control = 0,
+    /// The DIR channel status and user data buffers.
+    ReceiverBuffers = 1,
+    /// The DIT channel status and user data buffers.
+    TransmitterBuffers = 2,
+}
+
 impl AsAddressByte for Registers {
     fn as_address_byte(&self, rw: ReadWrite) -> u8 {
         match rw {
@@ -3,6 +3,7 @@ use packed_struct::prelude::*;
 use crate::{RegisterAddress, Registers};
 
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
 pub struct Reset {
     #[packed_field(bits = "0")]
@@ -18,6 +19,7 @@ pub struct Reset {
     #[packed_field(bits = "5")]
     pub n_pdall: bool,
     #[packed_field(bits = "6")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _reserved: ReservedZero<packed_bits::Bits<1>>,
     #[packed_field(bits = "7")]
     pub reset: bool,
@@ -29,4 +31,8 @@ impl RegisterAddress<Registers> for Reset {
     fn register_address() -> Registers {
         Self::ADDRESS
     }
+
+    fn self_clearing_bits() -> &'static [u8] {
+        &[0b1000_0000]
+    }
 }
@@ -6,21 +6,26 @@ use crate::registers::Registers;
 pub use fixed::{types::extra::U11, FixedU16};
 
 #[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SrcSource {
     #[default]
     PortA = 0b00,
     PortB = 0b01,
     DIR = 0b10,
+    Reserved = 0b11,
 }
 #[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SrcClockSource {
     #[default]
     Mclk = 0b00,
     Rxcki = 0b01,
     Rxcko = 0b10,
+    Reserved = 0b11,
 }
 
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
 pub struct SrcControl1 {
     #[packed_field(bits = "0..2", ty = "enum")]
@@ -42,24 +47,34 @@ impl RegisterAddress<Registers> for SrcControl1 {
 }
 
 #[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InterpolationFilterGroupDelay {
     #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "64"))]
     _64 = 0b00,
+    #[cfg_attr(feature = "serde", serde(rename = "32"))]
     _32 = 0b01,
+    #[cfg_attr(feature = "serde", serde(rename = "16"))]
     _16 = 0b10,
+    #[cfg_attr(feature = "serde", serde(rename = "8"))]
     _8 = 0b11,
 }
 
 #[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Deemphasis {
     #[default]
     None = 0b00,
+    #[cfg_attr(feature = "serde", serde(rename = "48000Hz"))]
     _48000hz = 0b01,
+    #[cfg_attr(feature = "serde", serde(rename = "44100Hz"))]
     _44100Hz = 0b10,
+    #[cfg_attr(feature = "serde", serde(rename = "32000Hz"))]
     _32000Hz = 0b11,
 }
 
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
 pub struct SrcControl2 {
     #[packed_field(bits = "0..2", ty = "enum")]
@@ -78,7 +93,8 @@ pub struct SrcControl2 {
     #[packed_field(bits = "5")]
     pub autodem: bool,
     #[packed_field(bits = "6..8")]
-    _reserved: ReservedZeroes<packed_bits::Bits<2>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZeroes<packed_bits::Bits<2>>,
 }
 impl SrcControl2 {
     pub const REGISTER_ADDRESS: Registers = Registers::SrcControl2;
@@ -90,19 +106,26 @@ impl RegisterAddress<Registers> for SrcControl2 {
 }
 
 #[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SrcOutputWordLength {
     #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "24bits"))]
     _24bits = 0b00,
+    #[cfg_attr(feature = "serde", serde(rename = "20bits"))]
     _20bits = 0b01,
+    #[cfg_attr(feature = "serde", serde(rename = "18bits"))]
     _18bits = 0b10,
+    #[cfg_attr(feature = "serde", serde(rename = "16bits"))]
     _16bits = 0b11,
 }
 
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
 pub struct SrcControl3 {
     #[packed_field(bits = "0..6")]
-    _reserved: ReservedZeroes<packed_bits::Bits<6>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZeroes<packed_bits::Bits<6>>,
     #[packed_field(bits = "6..8", ty = "enum")]
     pub output_word_length: SrcOutputWordLength,
 }
@@ -115,7 +138,42 @@ impl RegisterAddress<Registers> for SrcControl3 {
     }
 }
 
+/// Left channel output attenuation in 0.5dB steps. 0 is no attenuation and 255 is -127.5dB.
 #[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct SrcControl4 {
+    #[packed_field(bits = "0..8")]
+    pub attenuation: u8,
+}
+impl SrcControl4 {
+    pub const REGISTER_ADDRESS: Registers = Registers::SrcControl4;
+}
+impl RegisterAddress<Registers> for SrcControl4 {
+    fn register_address() -> crate::registers::Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+/// Right channel output attenuation in 0.5dB steps. 0 is no attenuation and 255 is -127.5dB.
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct SrcControl5 {
+    #[packed_field(bits = "0..8")]
+    pub attenuation: u8,
+}
+impl SrcControl5 {
+    pub const REGISTER_ADDRESS: Registers = Registers::SrcControl5;
+}
+impl RegisterAddress<Registers> for SrcControl5 {
+    fn register_address() -> crate::registers::Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "2")]
 pub struct SrcRatio {
     #[packed_field(bits = "11..16")]
@@ -133,6 +191,43 @@ impl SrcRatio {
     pub fn as_f32(&self) -> f32 {
         self.as_fixed().into()
     }
+
+    pub fn from_fixed(ratio: FixedU16<U11>) -> Self {
+        // Every bit pattern is a valid ratio.
+        SrcRatio::unpack(&ratio.to_be_bytes()).unwrap()
+    }
+
+    /// The ratio as a numerator and denominator in lowest terms.
+    pub fn as_rational(&self) -> (u32, u32) {
+        let numerator = u32::from(self.as_fixed().to_bits());
+        if numerator == 0 {
+            return (0, 1);
+        }
+        let shift = numerator.trailing_zeros().min(11);
+        (numerator >> shift, (1 << 11) >> shift)
+    }
+
+    /// The input rate, rounded to the nearest Hz, when the SRC output runs at `output_hz`.
+    /// `None` when it doesn't fit in a `u32`.
+    pub fn input_hz(&self, output_hz: u32) -> Option<u32> {
+        ratio_input_hz(u64::from(self.as_fixed().to_bits()), 11, output_hz)
+    }
+
+    /// How far the ratio is from `expected`, in parts per million of `expected`. One step of the
+    /// ratio is about 490 ppm at 1:1. `None` when `expected` is zero or the difference doesn't
+    /// fit in an `i32`.
+    pub fn ppm_from(&self, expected: FixedU16<U11>) -> Option<i32> {
+        ppm(
+            u64::from(self.as_fixed().to_bits()),
+            u64::from(expected.to_bits()),
+        )
+    }
+
+    /// Whether the ratio is within `tolerance_ppm` of `expected`.
+    pub fn is_within(&self, expected: FixedU16<U11>, tolerance_ppm: u32) -> bool {
+        self.ppm_from(expected)
+            .is_some_and(|ppm| ppm.unsigned_abs() <= tolerance_ppm)
+    }
 }
 impl RegisterAddress<Registers> for SrcRatio {
     fn register_address() -> crate::registers::Registers {
@@ -140,6 +235,25 @@ impl RegisterAddress<Registers> for SrcRatio {
     }
 }
 
+/// The input rate, rounded to the nearest Hz, for an input to output `ratio` with
+/// `fraction_bits` fractional bits, at least 1. Filters that average the ratio register keep
+/// more fractional bits than its 11. `None` when the rate doesn't fit in a `u32`.
+pub(crate) fn ratio_input_hz(ratio: u64, fraction_bits: u32, output_hz: u32) -> Option<u32> {
+    let scaled = ratio.checked_mul(u64::from(output_hz))?;
+    let rounded = scaled.checked_add(1 << (fraction_bits - 1))? >> fraction_bits;
+    u32::try_from(rounded).ok()
+}
+
+/// How far `value` is from `expected`, in parts per million of `expected`. `None` when `expected`
+/// is zero or the difference doesn't fit in an `i32`.
+pub(crate) fn ppm(value: u64, expected: u64) -> Option<i32> {
+    if expected == 0 {
+        return None;
+    }
+    let difference = i128::from(value) - i128::from(expected);
+    i32::try_from(difference * 1_000_000 / i128::from(expected)).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,4 +292,24 @@ mod tests {
         assert_eq!(fixed.int(), 5);
         assert_eq!(fixed.frac(), (567f32 / 2u16.pow(11) as f32));
     }
+    #[test]
+    fn src_ratio_integer_helpers() {
+        let ratio = |bits| SrcRatio::from_fixed(FixedU16::<U11>::from_bits(bits));
+        assert_eq!(ratio(4096).as_rational(), (2, 1));
+        assert_eq!(ratio(1536).as_rational(), (3, 4));
+        assert_eq!(ratio(1882).as_rational(), (941, 1024));
+        assert_eq!(ratio(0).as_rational(), (0, 1));
+
+        assert_eq!(ratio(1882).input_hz(48_000), Some(44_109));
+        assert_eq!(ratio(4096).input_hz(96_000), Some(192_000));
+        assert_eq!(ratio(0xFFFF).input_hz(u32::MAX), None);
+
+        let one = FixedU16::<U11>::from_num(1);
+        assert_eq!(ratio(2049).ppm_from(one), Some(488));
+        assert_eq!(ratio(2047).ppm_from(one), Some(-488));
+        assert_eq!(ratio(2048).ppm_from(FixedU16::<U11>::ZERO), None);
+        assert_eq!(ratio(0xFFFF).ppm_from(FixedU16::<U11>::from_bits(1)), None);
+        assert!(ratio(2049).is_within(one, 500));
+        assert!(!ratio(2050).is_within(one, 500));
+    }
 }
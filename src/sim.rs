@@ -0,0 +1,801 @@
+//! An in-memory, register level model of the SRC4392 for testing without hardware.
+//!
+//! [`SimulatedSrc4392`] decodes SPI frames the way the device does: the first byte of a frame
+//! holds the read bit and the start address, the second byte is ignored and every byte after
+//! that reads or writes one register, incrementing the address as it goes. The chip select and
+//! SPI traits are implemented on a shared reference so the same simulator can be handed to
+//! [`crate::Src4392::new`] as the chip select pin and passed to every call as the SPI bus.
+//!
+//! Beyond the register file the simulator can play out scripted [`Event`]s, such as the DIR
+//! locking or the input switching rate. Status, ratio and preamble registers follow the events
+//! and the interrupt output is driven according to the interrupt masks and modes written to the
+//! device. Time only moves when [`SimulatedSrc4392::advance_ms`] is called.
+//!
+//! ```
+//! use src4392::sim::{NoDelay, SimulatedSrc4392};
+//! use src4392::Src4392;
+//!
+//! let sim = SimulatedSrc4392::new();
+//! let mut spi = &sim;
+//! let mut src4392 = Src4392::new(&sim, NoDelay);
+//! src4392.reset(&mut spi).unwrap();
+//! ```
+
+use core::cell::RefCell;
+use core::convert::Infallible;
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::spi::Transfer;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::dump::BUFFER_PAGE_LEN;
+use crate::registers::{Page, Registers};
+
+/// A [`DelayMs`] that returns straight away.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NoDelay;
+
+impl DelayMs<u8> for NoDelay {
+    fn delay_ms(&mut self, _ms: u8) {}
+}
+
+/// Something that happens to the simulated device's inputs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The DIR locks to an input at `rate_hz`. The SRC measures the new ratio and becomes ready.
+    Lock { rate_hz: u32 },
+    /// The DIR loses its input.
+    Unlock,
+    /// The source switches rate: the DIR loses lock, relocks at `rate_hz` and the received
+    /// channel status changes.
+    RateChange { rate_hz: u32 },
+    /// The DIR sees parity errors for `duration_ms`.
+    ParityErrors { duration_ms: u32 },
+    /// Ends [`Event::ParityErrors`]. Scheduled automatically.
+    ParityCleared,
+    /// An IEC 61937 non-PCM stream appears, with the given burst info data type and payload
+    /// length in bits.
+    NonPcm { data_type: u8, length_bits: u16 },
+    /// The input goes back to PCM audio.
+    Pcm,
+}
+
+impl Event {
+    /// An AC-3 stream, IEC 61937 data type 1, at the usual 448kbps frame size.
+    pub const AC3: Event = Event::NonPcm {
+        data_type: 1,
+        length_bits: 0x3800,
+    };
+}
+
+/// Where an interrupt source lives in the status, mask and mode registers.
+struct InterruptSource {
+    status: Registers,
+    bit: u8,
+    mask: Registers,
+    mode: Registers,
+    mode_shift: u8,
+    /// The bit set in [`Registers::GlobalInterruptStatus`].
+    global: u8,
+}
+
+const GLOBAL_SRC: u8 = 0b001;
+const GLOBAL_RX: u8 = 0b010;
+const GLOBAL_TX: u8 = 0b100;
+
+const fn source(
+    status: Registers,
+    bit: u8,
+    mask: Registers,
+    mode: Registers,
+    mode_shift: u8,
+    global: u8,
+) -> InterruptSource {
+    InterruptSource {
+        status,
+        bit,
+        mask,
+        mode,
+        mode_shift,
+        global,
+    }
+}
+
+const SOURCES: [InterruptSource; 14] = [
+    source(
+        Registers::SrcAndDitStatus,
+        1,
+        Registers::SrcAndDitInterruptMask,
+        Registers::SrcAndDitInterruptMode,
+        0,
+        GLOBAL_TX,
+    ),
+    source(
+        Registers::SrcAndDitStatus,
+        2,
+        Registers::SrcAndDitInterruptMask,
+        Registers::SrcAndDitInterruptMode,
+        2,
+        GLOBAL_TX,
+    ),
+    source(
+        Registers::SrcAndDitStatus,
+        3,
+        Registers::SrcAndDitInterruptMask,
+        Registers::SrcAndDitInterruptMode,
+        4,
+        GLOBAL_SRC,
+    ),
+    source(
+        Registers::SrcAndDitStatus,
+        4,
+        Registers::SrcAndDitInterruptMask,
+        Registers::SrcAndDitInterruptMode,
+        6,
+        GLOBAL_SRC,
+    ),
+    source(
+        Registers::ReceiverStatus,
+        0,
+        Registers::ReceiverInterruptMask,
+        Registers::ReceiverInterruptMode,
+        0,
+        GLOBAL_RX,
+    ),
+    source(
+        Registers::ReceiverStatus,
+        1,
+        Registers::ReceiverInterruptMask,
+        Registers::ReceiverInterruptMode,
+        2,
+        GLOBAL_RX,
+    ),
+    source(
+        Registers::ReceiverStatus,
+        2,
+        Registers::ReceiverInterruptMask,
+        Registers::ReceiverInterruptMode,
+        4,
+        GLOBAL_RX,
+    ),
+    source(
+        Registers::ReceiverStatus,
+        3,
+        Registers::ReceiverInterruptMask,
+        Registers::ReceiverInterruptMode,
+        6,
+        GLOBAL_RX,
+    ),
+    source(
+        Registers::ReceiverStatus,
+        4,
+        Registers::ReceiverInterruptMask,
+        Registers::ReceiverInterruptMode2,
+        0,
+        GLOBAL_RX,
+    ),
+    source(
+        Registers::ReceiverStatus,
+        5,
+        Registers::ReceiverInterruptMask,
+        Registers::ReceiverInterruptMode2,
+        2,
+        GLOBAL_RX,
+    ),
+    source(
+        Registers::ReceiverStatus,
+        6,
+        Registers::ReceiverInterruptMask,
+        Registers::ReceiverInterruptMode2,
+        4,
+        GLOBAL_RX,
+    ),
+    source(
+        Registers::ReceiverStatus,
+        7,
+        Registers::ReceiverInterruptMask,
+        Registers::ReceiverInterruptMode2,
+        6,
+        GLOBAL_RX,
+    ),
+    source(
+        Registers::ReceiverStatus2,
+        0,
+        Registers::ReceiverInterruptMask2,
+        Registers::ReceiverInterruptMode3,
+        0,
+        GLOBAL_RX,
+    ),
+    source(
+        Registers::ReceiverStatus2,
+        1,
+        Registers::ReceiverInterruptMask2,
+        Registers::ReceiverInterruptMode3,
+        2,
+        GLOBAL_RX,
+    ),
+];
+
+const READY: usize = 2;
+const RATIO: usize = 3;
+const PARITY: usize = 8;
+const UNLOCK: usize = 11;
+const CSCHG: usize = 13;
+
+const EVENT_CAPACITY: usize = 16;
+
+#[derive(Debug)]
+struct State {
+    control: [u8; 0x80],
+    receiver_buffers: [u8; BUFFER_PAGE_LEN],
+    transmitter_buffers: [u8; BUFFER_PAGE_LEN],
+    selected: bool,
+    /// Bytes clocked in the current frame.
+    position: usize,
+    read: bool,
+    address: u8,
+    /// One bit per entry of [`SOURCES`], set while the condition holds.
+    conditions: u16,
+    /// One bit per entry of [`SOURCES`], set when an edge was latched.
+    pending: u16,
+    output_rate_hz: u32,
+    now_ms: u32,
+    events: [Option<(u32, Event)>; EVENT_CAPACITY],
+    /// Page 0 bits that read as zero whatever is written.
+    stuck_low: [u8; 0x80],
+}
+
+impl State {
+    fn mode(&self, source: usize) -> u8 {
+        let source = &SOURCES[source];
+        (self.control[source.mode as usize] >> source.mode_shift) & 0b11
+    }
+
+    /// The status bit of a source, as the host would read it.
+    fn status(&self, source: usize) -> bool {
+        match self.mode(source) {
+            0b10 => self.conditions & (1 << source) != 0,
+            _ => self.pending & (1 << source) != 0,
+        }
+    }
+
+    fn enabled(&self, source: usize) -> bool {
+        let entry = &SOURCES[source];
+        self.control[entry.mask as usize] & (1 << entry.bit) != 0
+    }
+
+    fn set_condition(&mut self, source: usize, value: bool) {
+        let old = self.conditions & (1 << source) != 0;
+        if old != value {
+            let latch = match self.mode(source) {
+                0b01 => !value,
+                0b10 => false,
+                _ => value,
+            };
+            if latch {
+                self.pending |= 1 << source;
+            }
+        }
+        if value {
+            self.conditions |= 1 << source;
+        } else {
+            self.conditions &= !(1 << source);
+        }
+    }
+
+    fn pulse(&mut self, source: usize) {
+        self.set_condition(source, true);
+        self.set_condition(source, false);
+    }
+
+    fn interrupt(&self) -> bool {
+        (0..SOURCES.len()).any(|source| self.enabled(source) && self.status(source))
+    }
+
+    /// The value of a page 0 register, with status registers worked out from the interrupt
+    /// sources.
+    fn peek(&self, address: u8) -> u8 {
+        if address == Registers::GlobalInterruptStatus as u8 {
+            return (0..SOURCES.len())
+                .filter(|source| self.enabled(*source) && self.status(*source))
+                .fold(0, |global, source| global | SOURCES[source].global);
+        }
+        let mut value = self.control[address as usize];
+        for (index, source) in SOURCES.iter().enumerate() {
+            if source.status as u8 == address {
+                value &= !(1 << source.bit);
+                if self.status(index) {
+                    value |= 1 << source.bit;
+                }
+            }
+        }
+        value
+    }
+
+    fn set_input_rate(&mut self, rate_hz: Option<u32>) {
+        let ratio = match rate_hz {
+            Some(rate_hz) => {
+                (((rate_hz as u64) << 11) / self.output_rate_hz.max(1) as u64).min(0xFFFF) as u16
+            }
+            None => 0,
+        };
+        let [high, low] = ratio.to_be_bytes();
+        self.control[Registers::SrcInputOutputRatio1 as usize] = high;
+        self.control[Registers::SrcInputOutputRatio2 as usize] = low;
+    }
+
+    fn schedule(&mut self, at_ms: u32, event: Event) {
+        let slot = self
+            .events
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .expect("the simulator event queue is full");
+        *slot = Some((at_ms, event));
+    }
+
+    fn apply(&mut self, event: Event) {
+        match event {
+            Event::Lock { rate_hz } => {
+                self.set_condition(UNLOCK, false);
+                self.set_input_rate(Some(rate_hz));
+                self.set_condition(RATIO, true);
+                self.set_condition(READY, true);
+            }
+            Event::Unlock => {
+                self.set_condition(UNLOCK, true);
+                self.set_condition(READY, false);
+                self.set_condition(RATIO, false);
+                self.set_input_rate(None);
+            }
+            Event::RateChange { rate_hz } => {
+                self.apply(Event::Unlock);
+                self.pulse(CSCHG);
+                self.apply(Event::Lock { rate_hz });
+            }
+            Event::ParityErrors { duration_ms } => {
+                self.set_condition(PARITY, true);
+                self.schedule(self.now_ms + duration_ms, Event::ParityCleared);
+            }
+            Event::ParityCleared => self.set_condition(PARITY, false),
+            Event::NonPcm {
+                data_type,
+                length_bits,
+            } => {
+                self.control[Registers::NonPcmAudioDetection as usize] = 0b01;
+                self.control[Registers::PcBurstPreambleHighByte as usize] = 0;
+                self.control[Registers::PcBurstPreambleLowByte as usize] = data_type & 0b1_1111;
+                let [high, low] = length_bits.to_be_bytes();
+                self.control[Registers::PdBurstPreambleHighByte as usize] = high;
+                self.control[Registers::PdBurstPreambleLowByte as usize] = low;
+            }
+            Event::Pcm => {
+                for register in [
+                    Registers::NonPcmAudioDetection,
+                    Registers::PcBurstPreambleHighByte,
+                    Registers::PcBurstPreambleLowByte,
+                    Registers::PdBurstPreambleHighByte,
+                    Registers::PdBurstPreambleLowByte,
+                ] {
+                    self.control[register as usize] = 0;
+                }
+            }
+        }
+    }
+
+    fn page(&self) -> u8 {
+        self.control[Registers::PageSelection as usize]
+    }
+
+    fn reset(&mut self) {
+        let page = self.page();
+        let ratio = [
+            self.control[Registers::SrcInputOutputRatio1 as usize],
+            self.control[Registers::SrcInputOutputRatio2 as usize],
+        ];
+        self.control = [0; 0x80];
+        self.control[Registers::PageSelection as usize] = page;
+        self.control[Registers::SrcInputOutputRatio1 as usize] = ratio[0];
+        self.control[Registers::SrcInputOutputRatio2 as usize] = ratio[1];
+        self.pending = 0;
+    }
+
+    /// Reads a register as the host does, which clears latched status bits.
+    fn read(&mut self, address: u8) -> u8 {
+        if address == Registers::PageSelection as u8 {
+            return self.page();
+        }
+        match self.page() {
+            p if p == Page::Control as u8 => {
+                let value = self.peek(address);
+                for (index, source) in SOURCES.iter().enumerate() {
+                    if source.status as u8 == address {
+                        self.pending &= !(1 << index);
+                    }
+                }
+                value
+            }
+            p if p == Page::ReceiverBuffers as u8 => self
+                .receiver_buffers
+                .get(address as usize)
+                .copied()
+                .unwrap_or(0),
+            p if p == Page::TransmitterBuffers as u8 => self
+                .transmitter_buffers
+                .get(address as usize)
+                .copied()
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, address: u8, value: u8) {
+        if address == Registers::PageSelection as u8 {
+            self.control[address as usize] = value & Registers::PageSelection.writable_bits();
+            return;
+        }
+        match self.page() {
+            p if p == Page::Control as u8 => {
+                let Some(register) = Registers::from_address(address) else {
+                    return;
+                };
+                let mask = register.writable_bits();
+                let current = &mut self.control[address as usize];
+                *current =
+                    ((*current & !mask) | (value & mask)) & !self.stuck_low[address as usize];
+                if register == Registers::Reset && value & 0b1000_0000 != 0 {
+                    self.reset();
+                }
+            }
+            p if p == Page::TransmitterBuffers as u8 => {
+                if let Some(byte) = self.transmitter_buffers.get_mut(address as usize) {
+                    *byte = value;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// See the [module documentation](self).
+#[derive(Debug)]
+pub struct SimulatedSrc4392 {
+    state: RefCell<State>,
+}
+
+impl Default for SimulatedSrc4392 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedSrc4392 {
+    /// A device in its reset state, with page 0 selected and no input, at an output rate of
+    /// 48kHz.
+    pub fn new() -> Self {
+        Self {
+            state: RefCell::new(State {
+                control: [0; 0x80],
+                receiver_buffers: [0; BUFFER_PAGE_LEN],
+                transmitter_buffers: [0; BUFFER_PAGE_LEN],
+                selected: false,
+                position: 0,
+                read: false,
+                address: 0,
+                conditions: 1 << UNLOCK,
+                pending: 0,
+                output_rate_hz: 48_000,
+                now_ms: 0,
+                events: [None; EVENT_CAPACITY],
+                stuck_low: [0; 0x80],
+            }),
+        }
+    }
+
+    /// The current value of a page 0 register. Unlike a read over SPI this doesn't clear
+    /// latched status bits.
+    pub fn register(&self, register: Registers) -> u8 {
+        self.state.borrow().peek(register as u8)
+    }
+
+    /// Sets a page 0 register, including read only bits. Status bits driven by [`Event`]s are
+    /// worked out on every read and can't be set this way.
+    pub fn set_register(&self, register: Registers, value: u8) {
+        self.state.borrow_mut().control[register as usize] = value;
+    }
+
+    /// Makes the `mask` bits of a page 0 register read as zero whatever is written, like a
+    /// faulty device or bus. Resets don't clear the fault.
+    pub fn set_stuck_low(&self, register: Registers, mask: u8) {
+        self.state.borrow_mut().stuck_low[register as usize] = mask;
+    }
+
+    pub fn receiver_buffers(&self) -> [u8; BUFFER_PAGE_LEN] {
+        self.state.borrow().receiver_buffers
+    }
+
+    /// Fakes the channel status and user data received by the DIR.
+    pub fn set_receiver_buffers(&self, buffers: [u8; BUFFER_PAGE_LEN]) {
+        self.state.borrow_mut().receiver_buffers = buffers;
+    }
+
+    pub fn transmitter_buffers(&self) -> [u8; BUFFER_PAGE_LEN] {
+        self.state.borrow().transmitter_buffers
+    }
+
+    /// The SRC output rate used to work out the input to output ratio.
+    pub fn set_output_rate(&self, rate_hz: u32) {
+        self.state.borrow_mut().output_rate_hz = rate_hz;
+    }
+
+    pub fn now_ms(&self) -> u32 {
+        self.state.borrow().now_ms
+    }
+
+    /// Plays out `event` straight away.
+    pub fn apply_event(&self, event: Event) {
+        self.state.borrow_mut().apply(event);
+    }
+
+    /// Plays out `event` once `after_ms` more milliseconds have been simulated.
+    ///
+    /// # Panics
+    /// If more than 16 events are waiting.
+    pub fn schedule(&self, after_ms: u32, event: Event) {
+        let mut state = self.state.borrow_mut();
+        let at_ms = state.now_ms + after_ms;
+        state.schedule(at_ms, event);
+    }
+
+    /// Moves simulated time forwards, playing out every event that falls due in order.
+    pub fn advance_ms(&self, ms: u32) {
+        let mut state = self.state.borrow_mut();
+        let until = state.now_ms + ms;
+        loop {
+            let next = state
+                .events
+                .iter_mut()
+                .filter(|slot| matches!(slot, Some((at_ms, _)) if *at_ms <= until))
+                .min_by_key(|slot| slot.map(|(at_ms, _)| at_ms));
+            let Some((at_ms, event)) = next.and_then(|slot| slot.take()) else {
+                break;
+            };
+            state.now_ms = at_ms;
+            state.apply(event);
+        }
+        state.now_ms = until;
+    }
+
+    /// Whether the interrupt output is asserted.
+    pub fn interrupt_asserted(&self) -> bool {
+        self.state.borrow().interrupt()
+    }
+}
+
+impl OutputPin for &SimulatedSrc4392 {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        let mut state = self.state.borrow_mut();
+        state.selected = true;
+        state.position = 0;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.state.borrow_mut().selected = false;
+        Ok(())
+    }
+}
+
+impl Transfer<u8> for &SimulatedSrc4392 {
+    type Error = Infallible;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        let mut state = self.state.borrow_mut();
+        if !state.selected {
+            return Ok(words);
+        }
+        for word in words.iter_mut() {
+            match state.position {
+                0 => {
+                    state.read = *word & 0b1000_0000 != 0;
+                    state.address = *word & 0b0111_1111;
+                    *word = 0;
+                }
+                1 => *word = 0,
+                _ => {
+                    let address = state.address;
+                    if state.read {
+                        *word = state.read(address);
+                    } else {
+                        state.write(address, *word);
+                        *word = 0;
+                    }
+                    state.address = address.wrapping_add(1) & 0b0111_1111;
+                }
+            }
+            state.position += 1;
+        }
+        Ok(words)
+    }
+}
+
+/// The driver and config the tests of every module run against the simulated device.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use core::convert::Infallible;
+
+    use super::{NoDelay, SimulatedSrc4392};
+    use crate::port_control::{AudioFormat, OutputDataSource};
+    use crate::sample_rate_converter::SrcSource;
+    use crate::{DeviceConfig, Port, PortConfig, PowerConfig, Src4392, SrcConfig};
+
+    pub(crate) type SimDevice<'a> =
+        Src4392<&'a SimulatedSrc4392, &'a SimulatedSrc4392, Infallible, NoDelay, u8>;
+
+    pub(crate) fn device(sim: &SimulatedSrc4392) -> SimDevice<'_> {
+        Src4392::new(sim, NoDelay)
+    }
+
+    /// Port A outputs the SRC, which tracks the DIR, with everything powered up.
+    pub(crate) fn config() -> DeviceConfig {
+        DeviceConfig::builder()
+            .power(PowerConfig::ALL_ON)
+            .port(
+                Port::A,
+                PortConfig {
+                    audio_format: AudioFormat::I2S,
+                    output_data_source: OutputDataSource::SRC,
+                    is_master: true,
+                    ..Default::default()
+                },
+            )
+            .src(SrcConfig {
+                source: SrcSource::DIR,
+                track: true,
+                ..Default::default()
+            })
+            .build()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixture::device;
+    use super::*;
+    use crate::interrupt::InterruptMode;
+    use crate::port_control::{AudioFormat, OutputDataSource, PortClockSource};
+    use crate::receiver::{
+        NonPcmAudioDetection, ReceiverInterruptMask1, ReceiverInterruptMode2, ReceiverStatus1,
+    };
+    use crate::{Port, ReadModifyWriteSpiRegister, SrcRatio};
+    use packed_struct::prelude::*;
+
+    #[test]
+    fn read_only_bits_ignore_writes() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        src4392
+            .configure_port(
+                &mut spi,
+                Port::B,
+                AudioFormat::I2S,
+                OutputDataSource::DIR,
+                Default::default(),
+                PortClockSource::Rxcki,
+                false,
+            )
+            .unwrap();
+        sim.set_register(Registers::SrcInputOutputRatio1, 0x12);
+        src4392
+            .write_registers(&mut spi, Registers::SrcInputOutputRatio1, &mut [0xFF])
+            .unwrap();
+        assert_eq!(sim.register(Registers::PortBControl2), 0b0000_0100);
+        assert_eq!(sim.register(Registers::SrcInputOutputRatio1), 0x12);
+    }
+
+    #[test]
+    fn lock_after_delay_sets_ratio() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        src4392
+            .modify_register(&mut spi, |reg: &mut ReceiverInterruptMode2| {
+                reg.unlockm = InterruptMode::Level
+            })
+            .unwrap();
+        sim.schedule(20, Event::Lock { rate_hz: 44_100 });
+        sim.advance_ms(19);
+        let mut status = [0u8];
+        src4392
+            .read_registers(&mut spi, Registers::ReceiverStatus, &mut status)
+            .unwrap();
+        assert!(ReceiverStatus1::unpack(&status).unwrap().unlock);
+
+        sim.advance_ms(1);
+        src4392
+            .read_registers(&mut spi, Registers::ReceiverStatus, &mut status)
+            .unwrap();
+        assert!(!ReceiverStatus1::unpack(&status).unwrap().unlock);
+        let mut ratio = [0u8; 2];
+        src4392
+            .read_registers(&mut spi, SrcRatio::REGISTER_ADDRESS, &mut ratio)
+            .unwrap();
+        let ratio = SrcRatio::unpack(&ratio).unwrap();
+        assert_eq!(ratio.as_fixed().to_bits() as u32, 44_100 * 2048 / 48_000);
+    }
+
+    #[test]
+    fn unlock_interrupt_latches_until_status_is_read() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        sim.apply_event(Event::Lock { rate_hz: 48_000 });
+        src4392
+            .modify_register(&mut spi, |reg: &mut ReceiverInterruptMask1| {
+                reg.munlock = true
+            })
+            .unwrap();
+        assert!(!sim.interrupt_asserted());
+
+        sim.schedule(5, Event::RateChange { rate_hz: 96_000 });
+        sim.advance_ms(10);
+        assert!(sim.interrupt_asserted());
+        assert_eq!(sim.register(Registers::GlobalInterruptStatus), 0b010);
+
+        let mut status = [0u8];
+        src4392
+            .read_registers(&mut spi, Registers::ReceiverStatus, &mut status)
+            .unwrap();
+        assert!(ReceiverStatus1::unpack(&status).unwrap().unlock);
+        assert!(!sim.interrupt_asserted());
+    }
+
+    #[test]
+    fn parity_burst_in_level_mode() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        src4392
+            .modify_register(&mut spi, |reg: &mut ReceiverInterruptMask1| {
+                reg.mparity = true
+            })
+            .unwrap();
+        src4392
+            .modify_register(&mut spi, |reg: &mut ReceiverInterruptMode2| {
+                reg.paritym = InterruptMode::Level
+            })
+            .unwrap();
+
+        sim.schedule(10, Event::ParityErrors { duration_ms: 5 });
+        sim.advance_ms(12);
+        assert!(sim.interrupt_asserted());
+        sim.advance_ms(3);
+        assert!(!sim.interrupt_asserted());
+    }
+
+    #[test]
+    fn ac3_stream_sets_preambles() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+
+        sim.apply_event(Event::AC3);
+        let mut detection = [0u8];
+        src4392
+            .read_registers(&mut spi, Registers::NonPcmAudioDetection, &mut detection)
+            .unwrap();
+        assert!(NonPcmAudioDetection::unpack(&detection).unwrap().iec61937);
+        let mut preambles = [0u8; 4];
+        src4392
+            .read_registers(&mut spi, Registers::PcBurstPreambleHighByte, &mut preambles)
+            .unwrap();
+        assert_eq!(preambles, [0x00, 0x01, 0x38, 0x00]);
+    }
+}
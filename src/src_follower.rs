@@ -0,0 +1,497 @@
+//! Reconfiguring the SRC when its input changes rate.
+//!
+//! Some SRC settings depend on the input rate: direct down sampling is only safe while the SRC
+//! upsamples and the de-emphasis filter has to match the rate the source was recorded at. A
+//! [`SrcFollower`] is polled on a schedule. It reads the measured ratio and, when the SRC takes
+//! its input from the DIR, the receiver lock status. Each time the input settles on a new
+//! standard rate the SRC is muted, [`SrcControl2`] is rewritten with the setup an [`SrcPolicy`]
+//! picks for that rate, and the SRC is unmuted once it reports ready. [`SrcFollower::stop`] hands
+//! the status bits the follower uses back to the application.
+//!
+//! The input has settled once several readings in a row, 3 by default, are close to the same
+//! standard rate. Readings that aren't close to any standard rate, such as those taken while the
+//! DIR is still locking, are ignored, so one bad reading never mutes the output or changes the
+//! filters. See [`SrcFollower::with_settling`].
+//!
+//! ```
+//! # #[cfg(feature = "sim")]
+//! # fn main() {
+//! use src4392::clock::{Rate, SampleRate};
+//! use src4392::sim::{Event, NoDelay, SimulatedSrc4392};
+//! use src4392::src_follower::{FollowerEvent, SafeSrcPolicy, SrcFollower};
+//! use src4392::Src4392;
+//!
+//! let sim = SimulatedSrc4392::new();
+//! let mut spi = &sim;
+//! let mut src4392 = Src4392::new(&sim, NoDelay);
+//! let mut follower = SrcFollower::new(SafeSrcPolicy::default(), SampleRate::from_hz(48_000));
+//! follower.start(&mut src4392, &mut spi).unwrap();
+//!
+//! sim.apply_event(Event::Lock { rate_hz: 44_100 });
+//! assert_eq!(follower.poll(&mut src4392, &mut spi).unwrap(), None);
+//! assert_eq!(follower.poll(&mut src4392, &mut spi).unwrap(), None);
+//! assert_eq!(
+//!     follower.poll(&mut src4392, &mut spi).unwrap(),
+//!     Some(FollowerEvent::Reconfigured(Rate::Hz44100))
+//! );
+//! assert_eq!(
+//!     follower.poll(&mut src4392, &mut spi).unwrap(),
+//!     Some(FollowerEvent::Ready(Rate::Hz44100))
+//! );
+//! # }
+//! # #[cfg(not(feature = "sim"))]
+//! # fn main() {}
+//! ```
+
+use core::fmt::Debug;
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::spi::Transfer;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::clock::{Rate, SampleRate};
+use crate::interrupt::{InterruptMode, SrcAndDitInterruptMode, SrcAndDitStatus};
+use crate::receiver::{ReceiverInterruptMode2, ReceiverStatus1};
+use crate::sample_rate_converter::{
+    Deemphasis, InterpolationFilterGroupDelay, SrcControl1, SrcControl2, SrcRatio, SrcSource,
+};
+use crate::{Error, ReadModifyWriteSpiRegister, Src4392};
+
+/// The rate dependent part of the SRC setup, written to [`SrcControl2`].
+///
+/// [`SrcControl1`] isn't part of it: the SRC input, its clock source and tracking follow from how
+/// the board is wired rather than from the input rate, so the follower only touches its mute.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SrcSetup {
+    pub interpolation_group_delay: InterpolationFilterGroupDelay,
+    pub direct_down_sampling: bool,
+    pub deemphasis: Deemphasis,
+    pub auto_deemphasis: bool,
+}
+
+/// Picks the SRC setup for an input rate.
+pub trait SrcPolicy {
+    fn setup(&self, input: Rate, output: SampleRate) -> SrcSetup;
+}
+
+impl<F> SrcPolicy for F
+where
+    F: Fn(Rate, SampleRate) -> SrcSetup,
+{
+    fn setup(&self, input: Rate, output: SampleRate) -> SrcSetup {
+        self(input, output)
+    }
+}
+
+/// A policy that keeps the setup valid for any rate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SafeSrcPolicy {
+    pub interpolation_group_delay: InterpolationFilterGroupDelay,
+    /// Use direct down sampling whenever the SRC upsamples. The decimation filter is used
+    /// otherwise.
+    pub prefer_direct_down_sampling: bool,
+    /// The source is pre-emphasised. The de-emphasis filter matching the input rate is used when
+    /// there is one, 32, 44.1 or 48 kHz, and turned off at other rates.
+    pub emphasised: bool,
+}
+
+impl Default for SafeSrcPolicy {
+    fn default() -> Self {
+        SafeSrcPolicy {
+            interpolation_group_delay: InterpolationFilterGroupDelay::_64,
+            prefer_direct_down_sampling: false,
+            emphasised: false,
+        }
+    }
+}
+
+impl SrcPolicy for SafeSrcPolicy {
+    fn setup(&self, input: Rate, output: SampleRate) -> SrcSetup {
+        let deemphasis = match (self.emphasised, input) {
+            (true, Rate::Hz32000) => Deemphasis::_32000Hz,
+            (true, Rate::Hz44100) => Deemphasis::_44100Hz,
+            (true, Rate::Hz48000) => Deemphasis::_48000hz,
+            _ => Deemphasis::None,
+        };
+        SrcSetup {
+            interpolation_group_delay: self.interpolation_group_delay,
+            direct_down_sampling: self.prefer_direct_down_sampling && output.hz() > input.hz(),
+            deemphasis,
+            auto_deemphasis: false,
+        }
+    }
+}
+
+/// What a [`SrcFollower::poll`] did.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FollowerEvent {
+    /// The input went away. The SRC was muted.
+    InputLost,
+    /// The input settled on a new rate. The SRC was muted and set up for it.
+    Reconfigured(Rate),
+    /// The SRC reported ready after a reconfiguration and was unmuted.
+    Ready(Rate),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    NoInput,
+    Settling(Rate),
+    Running(Rate),
+}
+
+/// Keeps the SRC set up for its input rate. See the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct SrcFollower<POLICY> {
+    policy: POLICY,
+    output: SampleRate,
+    state: State,
+    /// The SRC ready and DIR unlock interrupt modes from before [`SrcFollower::start`].
+    saved_modes: Option<(InterruptMode, InterruptMode)>,
+    settle_readings: u8,
+    tolerance_steps: u16,
+    /// A new rate seen in the last readings and how many readings in a row it was seen for.
+    candidate: Option<(Rate, u8)>,
+}
+
+impl<POLICY: SrcPolicy> SrcFollower<POLICY> {
+    /// A follower for an SRC running at `output`. The input has settled on a rate once 3
+    /// readings in a row are within 2 steps of the ratio register of it.
+    pub fn new(policy: POLICY, output: SampleRate) -> Self {
+        SrcFollower {
+            policy,
+            output,
+            state: State::NoInput,
+            saved_modes: None,
+            settle_readings: 3,
+            tolerance_steps: 2,
+            candidate: None,
+        }
+    }
+
+    /// The input has settled on a rate once `readings` readings in a row are within
+    /// `tolerance_steps` steps of the ratio register of that rate over the output rate. Readings
+    /// further than that from every standard rate are ignored.
+    ///
+    /// # Panics
+    ///
+    /// When `readings` is 0.
+    pub fn with_settling(mut self, readings: u8, tolerance_steps: u16) -> Self {
+        assert!(readings > 0, "settling needs at least one reading");
+        self.settle_readings = readings;
+        self.tolerance_steps = tolerance_steps;
+        self
+    }
+
+    /// The rate the SRC is currently set up for.
+    pub fn rate(&self) -> Option<Rate> {
+        match self.state {
+            State::NoInput => None,
+            State::Settling(rate) | State::Running(rate) => Some(rate),
+        }
+    }
+
+    /// Mutes the SRC until an input is found, and puts the SRC ready and DIR unlock status bits
+    /// in level mode so each poll sees the current state rather than latched edges. Those
+    /// status bits can't be used for edge triggered interrupts while following; their previous
+    /// modes are restored by [`SrcFollower::stop`].
+    pub fn start<P, SPI, E, D, DT>(
+        &mut self,
+        src4392: &mut Src4392<P, SPI, E, D, DT>,
+        spi: &mut SPI,
+    ) -> Result<(), Error<E>>
+    where
+        P: OutputPin,
+        P::Error: Debug,
+        SPI: Transfer<u8, Error = E>,
+        D: DelayMs<DT>,
+        DT: From<u8>,
+    {
+        let mut readym = InterruptMode::Level;
+        src4392.modify_register(spi, |reg: &mut SrcAndDitInterruptMode| {
+            readym = core::mem::replace(&mut reg.readym, InterruptMode::Level);
+        })?;
+        let mut unlockm = InterruptMode::Level;
+        src4392.modify_register(spi, |reg: &mut ReceiverInterruptMode2| {
+            unlockm = core::mem::replace(&mut reg.unlockm, InterruptMode::Level);
+        })?;
+        // Starting again mustn't save the level modes set by the first start.
+        self.saved_modes.get_or_insert((readym, unlockm));
+        src4392.modify_register(spi, |reg: &mut SrcControl1| reg.mute = true)?;
+        self.state = State::NoInput;
+        self.candidate = None;
+        Ok(())
+    }
+
+    /// Stops following and puts the SRC ready and DIR unlock interrupt modes back as they were
+    /// before [`SrcFollower::start`]. The SRC setup and mute are left as they are.
+    pub fn stop<P, SPI, E, D, DT>(
+        &mut self,
+        src4392: &mut Src4392<P, SPI, E, D, DT>,
+        spi: &mut SPI,
+    ) -> Result<(), Error<E>>
+    where
+        P: OutputPin,
+        P::Error: Debug,
+        SPI: Transfer<u8, Error = E>,
+        D: DelayMs<DT>,
+        DT: From<u8>,
+    {
+        if let Some((readym, unlockm)) = self.saved_modes {
+            src4392.modify_register(spi, |reg: &mut SrcAndDitInterruptMode| reg.readym = readym)?;
+            src4392.modify_register(spi, |reg: &mut ReceiverInterruptMode2| {
+                reg.unlockm = unlockm
+            })?;
+            self.saved_modes = None;
+        }
+        self.state = State::NoInput;
+        Ok(())
+    }
+
+    /// Checks the input and reconfigures the SRC if its rate changed. Returns what was done, if
+    /// anything.
+    pub fn poll<P, SPI, E, D, DT>(
+        &mut self,
+        src4392: &mut Src4392<P, SPI, E, D, DT>,
+        spi: &mut SPI,
+    ) -> Result<Option<FollowerEvent>, Error<E>>
+    where
+        P: OutputPin,
+        P::Error: Debug,
+        SPI: Transfer<u8, Error = E>,
+        D: DelayMs<DT>,
+        DT: From<u8>,
+    {
+        let control: SrcControl1 = src4392.read_register(spi)?;
+        let unlocked = control.source == SrcSource::DIR && {
+            let status: ReceiverStatus1 = src4392.read_register(spi)?;
+            status.unlock
+        };
+        let ratio = src4392.read_src_ratio(spi)?;
+        // A rate too large for a u32 can't be a real input.
+        let input = ratio.input_hz(self.output.hz()).unwrap_or(0);
+
+        if unlocked || input == 0 {
+            self.candidate = None;
+            if self.state == State::NoInput {
+                return Ok(None);
+            }
+            src4392.modify_register(spi, |reg: &mut SrcControl1| reg.mute = true)?;
+            self.state = State::NoInput;
+            return Ok(Some(FollowerEvent::InputLost));
+        }
+
+        let Some(rate) = self.standard_rate(ratio, input) else {
+            self.candidate = None;
+            return Ok(None);
+        };
+        if self.rate() == Some(rate) {
+            self.candidate = None;
+        }
+        match self.state {
+            State::Settling(current) if current == rate => {
+                let status: SrcAndDitStatus = src4392.read_register(spi)?;
+                if !status.ready {
+                    return Ok(None);
+                }
+                src4392.modify_register(spi, |reg: &mut SrcControl1| reg.mute = false)?;
+                self.state = State::Running(rate);
+                Ok(Some(FollowerEvent::Ready(rate)))
+            }
+            State::Running(current) if current == rate => Ok(None),
+            _ => {
+                let seen = match self.candidate {
+                    Some((candidate, seen)) if candidate == rate => seen.saturating_add(1),
+                    _ => 1,
+                };
+                if seen < self.settle_readings {
+                    self.candidate = Some((rate, seen));
+                    return Ok(None);
+                }
+                self.candidate = None;
+                let setup = self.policy.setup(rate, self.output);
+                src4392.modify_register(spi, |reg: &mut SrcControl1| reg.mute = true)?;
+                src4392.modify_register(spi, |reg: &mut SrcControl2| {
+                    reg.interpolation_group_delay = setup.interpolation_group_delay;
+                    reg.dd_n = setup.direct_down_sampling;
+                    reg.deemphasis = setup.deemphasis;
+                    reg.autodem = setup.auto_deemphasis;
+                })?;
+                self.state = State::Settling(rate);
+                Ok(Some(FollowerEvent::Reconfigured(rate)))
+            }
+        }
+    }
+
+    /// The standard rate `ratio` is within the tolerance of, if any. `input` is the rate worked
+    /// out from `ratio`.
+    fn standard_rate(&self, ratio: SrcRatio, input: u32) -> Option<Rate> {
+        let rate = Rate::nearest(SampleRate::from_hz(input));
+        let expected = rate.sample_rate().src_ratio(self.output)?.to_bits();
+        let reading = ratio.as_fixed().to_bits();
+        (reading.abs_diff(expected) <= self.tolerance_steps).then_some(rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registers::Registers;
+    use crate::sim::fixture::{device, SimDevice};
+    use crate::sim::{Event, SimulatedSrc4392};
+
+    fn muted(sim: &SimulatedSrc4392) -> bool {
+        sim.register(Registers::SrcControl) & 0b0001_0000 != 0
+    }
+
+    /// Polls until the follower has seen a rate for one reading less than it needs to settle.
+    fn poll_unsettled<'a, P: SrcPolicy>(
+        follower: &mut SrcFollower<P>,
+        src4392: &mut SimDevice<'a>,
+        mut spi: &'a SimulatedSrc4392,
+    ) {
+        for _ in 1..3 {
+            assert_eq!(follower.poll(src4392, &mut spi).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn follows_rate_changes() {
+        let sim = SimulatedSrc4392::new();
+        sim.set_output_rate(96_000);
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        src4392
+            .set_src(
+                &mut spi,
+                SrcSource::DIR,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                false,
+            )
+            .unwrap();
+        let policy = SafeSrcPolicy {
+            prefer_direct_down_sampling: true,
+            emphasised: true,
+            ..Default::default()
+        };
+        let mut follower = SrcFollower::new(policy, SampleRate::from_hz(96_000));
+        follower.start(&mut src4392, &mut spi).unwrap();
+        assert!(muted(&sim));
+        assert_eq!(follower.poll(&mut src4392, &mut spi).unwrap(), None);
+
+        sim.apply_event(Event::Lock { rate_hz: 44_100 });
+        poll_unsettled(&mut follower, &mut src4392, &sim);
+        assert_eq!(
+            follower.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FollowerEvent::Reconfigured(Rate::Hz44100))
+        );
+        // Upsampling with a 44.1 kHz emphasised source: DDN on, 44.1 kHz de-emphasis.
+        assert_eq!(sim.register(Registers::SrcControl2), 0b0001_0100);
+        assert_eq!(
+            follower.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FollowerEvent::Ready(Rate::Hz44100))
+        );
+        assert!(!muted(&sim));
+        assert_eq!(follower.poll(&mut src4392, &mut spi).unwrap(), None);
+
+        sim.apply_event(Event::Unlock);
+        assert_eq!(
+            follower.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FollowerEvent::InputLost)
+        );
+        assert!(muted(&sim));
+
+        sim.apply_event(Event::Lock { rate_hz: 192_000 });
+        poll_unsettled(&mut follower, &mut src4392, &sim);
+        assert_eq!(
+            follower.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FollowerEvent::Reconfigured(Rate::Hz192000))
+        );
+        // Downsampling: the decimation filter and no de-emphasis.
+        assert_eq!(sim.register(Registers::SrcControl2), 0);
+        assert_eq!(follower.rate(), Some(Rate::Hz192000));
+    }
+
+    #[test]
+    fn closure_policy() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        let policy = |_: Rate, _: SampleRate| SrcSetup {
+            interpolation_group_delay: InterpolationFilterGroupDelay::_16,
+            direct_down_sampling: false,
+            deemphasis: Deemphasis::None,
+            auto_deemphasis: true,
+        };
+        let mut follower = SrcFollower::new(policy, SampleRate::from_hz(48_000));
+        follower.start(&mut src4392, &mut spi).unwrap();
+
+        sim.apply_event(Event::Lock { rate_hz: 48_000 });
+        poll_unsettled(&mut follower, &mut src4392, &sim);
+        assert_eq!(
+            follower.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FollowerEvent::Reconfigured(Rate::Hz48000))
+        );
+        assert_eq!(sim.register(Registers::SrcControl2), 0b0010_0010);
+    }
+
+    #[test]
+    fn stop_restores_interrupt_modes() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        src4392
+            .modify_register(&mut spi, |reg: &mut ReceiverInterruptMode2| {
+                reg.unlockm = InterruptMode::FallingEdge
+            })
+            .unwrap();
+        let modes = |sim: &SimulatedSrc4392| {
+            (
+                sim.register(Registers::SrcAndDitInterruptMode),
+                sim.register(Registers::ReceiverInterruptMode2),
+            )
+        };
+        let before = modes(&sim);
+
+        let mut follower = SrcFollower::new(SafeSrcPolicy::default(), SampleRate::from_hz(48_000));
+        follower.start(&mut src4392, &mut spi).unwrap();
+        follower.start(&mut src4392, &mut spi).unwrap();
+        assert_ne!(modes(&sim), before);
+        follower.stop(&mut src4392, &mut spi).unwrap();
+        assert_eq!(modes(&sim), before);
+    }
+
+    #[test]
+    fn waits_for_matching_readings() {
+        let sim = SimulatedSrc4392::new();
+        let mut spi = &sim;
+        let mut src4392 = device(&sim);
+        let mut follower = SrcFollower::new(SafeSrcPolicy::default(), SampleRate::from_hz(48_000))
+            .with_settling(2, 2);
+        follower.start(&mut src4392, &mut spi).unwrap();
+        let control2 = sim.register(Registers::SrcControl2);
+
+        // 46 kHz is far from every standard rate, so it's ignored rather than taken as 44.1 or
+        // 48 kHz.
+        sim.apply_event(Event::Lock { rate_hz: 46_000 });
+        for _ in 0..4 {
+            assert_eq!(follower.poll(&mut src4392, &mut spi).unwrap(), None);
+        }
+        assert_eq!(follower.rate(), None);
+
+        // A rate that changes between readings never settles.
+        for rate_hz in [44_100, 96_000, 44_100, 96_000] {
+            sim.apply_event(Event::Lock { rate_hz });
+            assert_eq!(follower.poll(&mut src4392, &mut spi).unwrap(), None);
+        }
+        assert_eq!(sim.register(Registers::SrcControl2), control2);
+        assert!(muted(&sim));
+
+        assert_eq!(
+            follower.poll(&mut src4392, &mut spi).unwrap(),
+            Some(FollowerEvent::Reconfigured(Rate::Hz96000))
+        );
+    }
+}
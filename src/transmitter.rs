@@ -0,0 +1,126 @@
+use packed_struct::prelude::*;
+
+use crate::{registers::Registers, RegisterAddress};
+
+#[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransmitterInputSource {
+    #[default]
+    PortA = 0b00,
+    PortB = 0b01,
+    SRC = 0b10,
+    Reserved = 0b11,
+}
+
+#[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransmitterClockSource {
+    #[default]
+    Mclk = 0b0,
+    Rxcki = 0b1,
+}
+
+#[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransmitterClockDivider {
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "128"))]
+    _128 = 0b00,
+    #[cfg_attr(feature = "serde", serde(rename = "256"))]
+    _256 = 0b01,
+    #[cfg_attr(feature = "serde", serde(rename = "384"))]
+    _384 = 0b10,
+    #[cfg_attr(feature = "serde", serde(rename = "512"))]
+    _512 = 0b11,
+}
+
+#[derive(PrimitiveEnum, Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BypassMultiplexer {
+    #[default]
+    Rx1 = 0b00,
+    Rx2 = 0b01,
+    Rx3 = 0b10,
+    Rx4 = 0b11,
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct TransmitterControl1 {
+    /// Block start input/output. false: the block start is an output, true: an input.
+    #[packed_field(bits = "0")]
+    pub bssl: bool,
+    /// The validity bit transmitted with every sub frame.
+    #[packed_field(bits = "1")]
+    pub valid: bool,
+    #[packed_field(bits = "2")]
+    pub blsm: bool,
+    #[packed_field(bits = "3..5", ty = "enum")]
+    pub txis: TransmitterInputSource,
+    #[packed_field(bits = "5", ty = "enum")]
+    pub txclk: TransmitterClockSource,
+    #[packed_field(bits = "6..8", ty = "enum")]
+    pub txdiv: TransmitterClockDivider,
+}
+impl TransmitterControl1 {
+    pub const REGISTER_ADDRESS: Registers = Registers::TransmitterControl;
+}
+impl RegisterAddress<Registers> for TransmitterControl1 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct TransmitterControl2 {
+    /// Forces the TX+ and TX- line driver outputs low.
+    #[packed_field(bits = "0")]
+    pub txoff: bool,
+    #[packed_field(bits = "1")]
+    pub txmute: bool,
+    /// Forces the AESOUT CMOS output low.
+    #[packed_field(bits = "2")]
+    pub aesoff: bool,
+    #[packed_field(bits = "3")]
+    pub txbtd: bool,
+    /// false: the line driver is fed by the DIT, true: by the bypass multiplexer.
+    #[packed_field(bits = "4")]
+    pub ldmux: bool,
+    /// false: AESOUT is fed by the DIT, true: by the bypass multiplexer.
+    #[packed_field(bits = "5")]
+    pub aesmux: bool,
+    #[packed_field(bits = "6..8", ty = "enum")]
+    pub bypmux: BypassMultiplexer,
+}
+impl TransmitterControl2 {
+    pub const REGISTER_ADDRESS: Registers = Registers::TransmitterControl2;
+}
+impl RegisterAddress<Registers> for TransmitterControl2 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
+
+#[derive(Debug, Default, PackedStruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[packed_struct(bit_numbering = "lsb0", endian = "msb", size_bytes = "1")]
+pub struct TransmitterControl3 {
+    #[packed_field(bits = "0..2")]
+    pub txcus: Integer<u8, packed_bits::Bits<2>>,
+    #[packed_field(bits = "2")]
+    pub valsel: bool,
+    #[packed_field(bits = "3..8")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _reserved: ReservedZeroes<packed_bits::Bits<5>>,
+}
+impl TransmitterControl3 {
+    pub const REGISTER_ADDRESS: Registers = Registers::TransmitterControl3;
+}
+impl RegisterAddress<Registers> for TransmitterControl3 {
+    fn register_address() -> Registers {
+        Self::REGISTER_ADDRESS
+    }
+}
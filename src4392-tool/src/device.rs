@@ -0,0 +1,436 @@
+//! Commands that talk to a device, either over Linux spidev or to a simulated device.
+
+use std::fmt::{Debug, Write as _};
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand, ValueEnum};
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::spi::Transfer;
+use embedded_hal::digital::v2::OutputPin;
+use src4392::decode::decode_any;
+use src4392::dump::{FULL_DUMP_LEN, REGISTER_DUMP_LEN};
+use src4392::profile::ProfileError;
+use src4392::registers::Registers;
+use src4392::sim::{NoDelay, SimulatedSrc4392};
+use src4392::{
+    AudioFormat, Deemphasis, InterpolationFilterGroupDelay, OutputDataSource, Port,
+    PortClockSource, PortMasterClockDivider, ReadModifyWriteSpiRegister, Src4392, SrcClockSource,
+    SrcSource,
+};
+
+#[derive(Args)]
+pub struct Backend {
+    /// Talk to a simulated device instead of hardware.
+    #[arg(long, global = true)]
+    sim: bool,
+    /// Load the simulated device from this dump before the command and save it back afterwards,
+    /// so state carries over between runs. Implies --sim.
+    #[arg(long, global = true, value_name = "DUMP")]
+    sim_state: Option<PathBuf>,
+    #[cfg(feature = "linux")]
+    #[command(flatten)]
+    linux: LinuxBackend,
+}
+
+#[cfg(feature = "linux")]
+#[derive(Args)]
+struct LinuxBackend {
+    #[arg(long, global = true, default_value = "/dev/spidev0.0")]
+    spidev: PathBuf,
+    #[arg(long, global = true, default_value_t = 1_000_000)]
+    speed_hz: u32,
+    /// The GPIO character device of the chip select line.
+    #[arg(long, global = true, default_value = "/dev/gpiochip0")]
+    cs_chip: PathBuf,
+    #[arg(long, global = true, default_value_t = 8)]
+    cs_line: u32,
+    /// Drive the chip select through sysfs GPIO with this number instead.
+    #[arg(long, global = true)]
+    cs_sysfs: Option<u64>,
+}
+
+#[derive(Subcommand)]
+pub enum DeviceCommand {
+    /// Resets every register to its default.
+    Reset,
+    /// Configures an audio port.
+    Port(PortArgs),
+    /// Configures the sample rate converter.
+    Src(SrcArgs),
+    /// Checks a TOML device profile and writes it, changing only the registers that differ.
+    Apply { profile: PathBuf },
+    /// Prints the status registers. Reading them clears latched interrupt bits.
+    Status,
+    /// Dumps the registers, as hex text unless --out is given.
+    Dump {
+        /// Include the DIR and DIT buffer pages.
+        #[arg(long)]
+        buffers: bool,
+        /// Write a binary dump to this file.
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum PortName {
+    A,
+    B,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum Format {
+    LeftJustified,
+    I2s,
+    Rj16,
+    Rj18,
+    Rj20,
+    Rj24,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum PortSource {
+    Loopback,
+    OtherPort,
+    Dir,
+    Src,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum Clock {
+    Mclk,
+    Rxcki,
+    Rxcko,
+}
+
+#[derive(Args)]
+pub struct PortArgs {
+    port: PortName,
+    #[arg(long)]
+    format: Format,
+    #[arg(long)]
+    source: PortSource,
+    /// Drive LRCK and BCK from the port instead of taking them as inputs.
+    #[arg(long)]
+    master: bool,
+    /// The master clock divider, 128, 256, 384 or 512.
+    #[arg(long, default_value_t = 128)]
+    divider: u16,
+    #[arg(long, value_enum, default_value_t = Clock::Mclk)]
+    clock: Clock,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum SrcInput {
+    PortA,
+    PortB,
+    Dir,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum DeemphasisArg {
+    None,
+    #[value(name = "48k")]
+    _48k,
+    #[value(name = "44.1k")]
+    _44k1,
+    #[value(name = "32k")]
+    _32k,
+}
+
+#[derive(Args)]
+pub struct SrcArgs {
+    #[arg(long)]
+    source: SrcInput,
+    #[arg(long, value_enum, default_value_t = Clock::Mclk)]
+    clock: Clock,
+    /// The interpolation filter group delay in samples, 64, 32, 16 or 8.
+    #[arg(long, default_value_t = 64)]
+    delay: u8,
+    #[arg(long, value_enum, default_value_t = DeemphasisArg::None)]
+    deemphasis: DeemphasisArg,
+    /// Use direct down sampling instead of the decimation filter.
+    #[arg(long)]
+    ddn: bool,
+}
+
+/// Runs `command` on the backend chosen by `backend`, returning the text to print.
+pub fn execute(backend: &Backend, command: &DeviceCommand) -> Result<String, String> {
+    if backend.sim || backend.sim_state.is_some() {
+        return execute_sim(backend, command);
+    }
+    #[cfg(feature = "linux")]
+    {
+        use src4392::linux::{self, ChipSelect, Delay};
+        let linux = &backend.linux;
+        let mut spi = linux::open_spi(&linux.spidev, linux.speed_hz)
+            .map_err(|e| format!("{}: {}", linux.spidev.display(), e))?;
+        let chip_select = match linux.cs_sysfs {
+            Some(pin) => ChipSelect::sysfs(pin),
+            None => ChipSelect::cdev(&linux.cs_chip, linux.cs_line),
+        }
+        .map_err(|e| format!("chip select: {:?}", e))?;
+        let mut src4392: linux::LinuxSrc4392 = Src4392::new(chip_select, Delay);
+        run(&mut src4392, &mut spi, command)
+    }
+    #[cfg(not(feature = "linux"))]
+    Err("built without the linux feature, only --sim is available".into())
+}
+
+fn execute_sim(backend: &Backend, command: &DeviceCommand) -> Result<String, String> {
+    let sim = SimulatedSrc4392::new();
+    let mut spi = &sim;
+    let mut src4392 = Src4392::new(&sim, NoDelay);
+
+    let state = backend.sim_state.as_ref();
+    if let Some(path) = state.filter(|path| path.exists()) {
+        let dump = crate::load(path)?;
+        src4392
+            .restore(&mut spi, &dump)
+            .map_err(|e| format!("restoring {}: {:?}", path.display(), e))?;
+    }
+    let out = run(&mut src4392, &mut spi, command)?;
+    if let Some(path) = state {
+        let mut dump = [0u8; FULL_DUMP_LEN];
+        src4392
+            .dump(&mut spi, &mut dump)
+            .map_err(|e| format!("{:?}", e))?;
+        std::fs::write(path, dump).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(out)
+}
+
+/// Runs `command` on any device.
+pub fn run<P, SPI, E, D>(
+    src4392: &mut Src4392<P, SPI, E, D, u8>,
+    spi: &mut SPI,
+    command: &DeviceCommand,
+) -> Result<String, String>
+where
+    P: OutputPin,
+    P::Error: Debug,
+    SPI: Transfer<u8, Error = E>,
+    E: Debug,
+    D: DelayMs<u8>,
+{
+    let mut out = String::new();
+    match command {
+        DeviceCommand::Reset => src4392.reset(spi).map_err(|e| format!("{:?}", e))?,
+        DeviceCommand::Port(args) => {
+            let port = match args.port {
+                PortName::A => Port::A,
+                PortName::B => Port::B,
+            };
+            let format = match args.format {
+                Format::LeftJustified => AudioFormat::LeftJustified24bit,
+                Format::I2s => AudioFormat::I2S,
+                Format::Rj16 => AudioFormat::RightJustified16bit,
+                Format::Rj18 => AudioFormat::RightJustified18bit,
+                Format::Rj20 => AudioFormat::RightJustified20bit,
+                Format::Rj24 => AudioFormat::RightJustified24bit,
+            };
+            let source = match args.source {
+                PortSource::Loopback => OutputDataSource::Loopback,
+                PortSource::OtherPort => OutputDataSource::OtherPort,
+                PortSource::Dir => OutputDataSource::DIR,
+                PortSource::Src => OutputDataSource::SRC,
+            };
+            let divider = match args.divider {
+                128 => PortMasterClockDivider::_128,
+                256 => PortMasterClockDivider::_256,
+                384 => PortMasterClockDivider::_384,
+                512 => PortMasterClockDivider::_512,
+                other => return Err(format!("{} isn't a port clock divider", other)),
+            };
+            let clock = match args.clock {
+                Clock::Mclk => PortClockSource::Mclk,
+                Clock::Rxcki => PortClockSource::Rxcki,
+                Clock::Rxcko => PortClockSource::Rxcko,
+            };
+            src4392
+                .configure_port(spi, port, format, source, divider, clock, args.master)
+                .map_err(|e| format!("{:?}", e))?
+        }
+        DeviceCommand::Src(args) => {
+            let source = match args.source {
+                SrcInput::PortA => SrcSource::PortA,
+                SrcInput::PortB => SrcSource::PortB,
+                SrcInput::Dir => SrcSource::DIR,
+            };
+            let clock = match args.clock {
+                Clock::Mclk => SrcClockSource::Mclk,
+                Clock::Rxcki => SrcClockSource::Rxcki,
+                Clock::Rxcko => SrcClockSource::Rxcko,
+            };
+            let delay = match args.delay {
+                64 => InterpolationFilterGroupDelay::_64,
+                32 => InterpolationFilterGroupDelay::_32,
+                16 => InterpolationFilterGroupDelay::_16,
+                8 => InterpolationFilterGroupDelay::_8,
+                other => return Err(format!("{} isn't an interpolation group delay", other)),
+            };
+            let deemphasis = match args.deemphasis {
+                DeemphasisArg::None => Deemphasis::None,
+                DeemphasisArg::_48k => Deemphasis::_48000hz,
+                DeemphasisArg::_44k1 => Deemphasis::_44100Hz,
+                DeemphasisArg::_32k => Deemphasis::_32000Hz,
+            };
+            src4392
+                .set_src(spi, source, clock, delay, deemphasis, args.ddn)
+                .map_err(|e| format!("{:?}", e))?
+        }
+        DeviceCommand::Apply { profile } => {
+            let text = std::fs::read_to_string(profile)
+                .map_err(|e| format!("{}: {}", profile.display(), e))?;
+            let config = src4392::profile::from_toml(&text).map_err(|e| match e {
+                ProfileError::Parse(e) => format!("{}: {}", profile.display(), e),
+                ProfileError::Config(e) => format!("{}: {:?}", profile.display(), e),
+            })?;
+            src4392
+                .apply(spi, &config)
+                .map_err(|e| format!("{:?}", e))?
+        }
+        DeviceCommand::Status => {
+            const STATUS: [Registers; 8] = [
+                Registers::GlobalInterruptStatus,
+                Registers::SrcAndDitStatus,
+                Registers::NonPcmAudioDetection,
+                Registers::ReceiverStatus,
+                Registers::ReceiverStatus2,
+                Registers::ReceiverStatus3,
+                Registers::SrcInputOutputRatio1,
+                Registers::SrcInputOutputRatio2,
+            ];
+            let mut values = [0u8; STATUS.len()];
+            for (register, value) in STATUS.iter().zip(values.iter_mut()) {
+                let mut buffer = [0u8];
+                src4392
+                    .read_registers(spi, *register, &mut buffer)
+                    .map_err(|e| format!("{:?}", e))?;
+                *value = buffer[0];
+                writeln!(out, "{}", decode_any(*register as u8, *value)).unwrap();
+            }
+            let ratio = u16::from_be_bytes([values[6], values[7]]);
+            writeln!(out, "SRC ratio: {:.4}", f32::from(ratio) / 2048.0).unwrap();
+        }
+        DeviceCommand::Dump { buffers, out: path } => {
+            let mut dump = [0u8; FULL_DUMP_LEN];
+            let len = if *buffers {
+                src4392.dump(spi, &mut dump)
+            } else {
+                let mut registers = [0u8; REGISTER_DUMP_LEN];
+                src4392.dump(spi, &mut registers).inspect(|_| {
+                    dump[..REGISTER_DUMP_LEN].copy_from_slice(&registers);
+                })
+            }
+            .map_err(|e| format!("{:?}", e))?;
+            match path {
+                Some(path) => std::fs::write(path, &dump[..len])
+                    .map_err(|e| format!("{}: {}", path.display(), e))?,
+                None => {
+                    for line in dump[..len].chunks(16) {
+                        let line: Vec<_> = line.iter().map(|b| format!("{:02X}", b)).collect();
+                        writeln!(out, "{}", line.join(" ")).unwrap();
+                    }
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A path in the temp dir unique to this test run, removed when dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let name = format!("src4392-tool-{}-{}", std::process::id(), name);
+            let path = std::env::temp_dir().join(name);
+            let _ = std::fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn execute_args(args: &[&str]) -> Result<String, String> {
+        let cli = crate::Cli::try_parse_from(args).map_err(|e| e.to_string())?;
+        match cli.command {
+            crate::Command::Device(command) => execute(&cli.backend, &command),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn state_carries_over_between_runs() {
+        let state = TempFile::new("carry.bin");
+        let state = state.0.to_str().unwrap();
+        let run = |args: &[&str]| {
+            let mut full = vec!["src4392-tool", "--sim-state", state];
+            full.extend(args);
+            execute_args(&full).unwrap()
+        };
+        run(&["reset"]);
+        run(&[
+            "port", "a", "--format", "i2s", "--source", "src", "--master",
+        ]);
+        run(&["src", "--source", "dir", "--delay", "32"]);
+
+        let dump = crate::load(state.as_ref()).unwrap();
+        let decoded = crate::decode(&dump);
+        assert!(decoded.contains("PortAControl(0x03)=0x39"));
+        assert!(decoded.contains("SrcControl2(0x2E)=0x01: IGRP=32"));
+        assert!(run(&["status"]).contains("ReceiverStatus(0x13)"));
+    }
+
+    #[test]
+    fn apply_profile() {
+        let state = TempFile::new("profile.bin");
+        let state = &state.0;
+        let profile = TempFile::new("profile.toml");
+        let profile = &profile.0;
+        std::fs::write(
+            profile,
+            "[power]\nport_a = true\n\n[port_a]\naudio_format = \"I2S\"\nis_master = true\n",
+        )
+        .unwrap();
+        let run = |command: &str| {
+            execute_args(&[
+                "src4392-tool",
+                "--sim-state",
+                state.to_str().unwrap(),
+                command,
+                profile.to_str().unwrap(),
+            ])
+        };
+        run("apply").unwrap();
+        let decoded = crate::decode(&crate::load(state).unwrap());
+        assert!(decoded.contains("PortAControl(0x03)=0x09"));
+
+        std::fs::write(profile, "[port_a]\nformat = \"I2S\"\n").unwrap();
+        assert!(run("apply").unwrap_err().contains("unknown field `format`"));
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        let error = execute_args(&[
+            "src4392-tool",
+            "--sim",
+            "src",
+            "--source",
+            "dir",
+            "--delay",
+            "7",
+        ]);
+        assert_eq!(error.unwrap_err(), "7 isn't an interpolation group delay");
+    }
+}
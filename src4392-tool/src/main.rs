@@ -0,0 +1,344 @@
+//! Decodes, diffs and checks SRC4392 register dumps taken with `Src4392::dump`, and controls a
+//! device over Linux spidev, or a simulated one with `--sim`.
+//!
+//! Dumps are read either in the binary layout described in `src4392::dump`, or as hex text. Hex
+//! text may hold a whole dump, header included, or just the page 0 registers starting from
+//! address 0x00. Bytes are separated by whitespace or commas, may have a `0x` prefix and `#`
+//! starts a comment.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use device::{Backend, DeviceCommand};
+use src4392::config::DecodeError;
+use src4392::decode::decode_any;
+use src4392::dump::{self, RegisterDump};
+use src4392::registers::Registers;
+use src4392::{ConfigError, DeviceConfig, Port};
+
+mod device;
+
+#[derive(Parser)]
+#[command(about)]
+struct Cli {
+    #[command(flatten)]
+    backend: Backend,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints every register of a dump with its fields.
+    Decode { dump: PathBuf },
+    /// Prints the registers and fields that differ between two dumps.
+    Diff { old: PathBuf, new: PathBuf },
+    /// Looks for setups that can't work, such as a port outputting a powered down block.
+    Check {
+        dump: PathBuf,
+        /// The nominal SRC input rate in Hz. Direct down sampling is only checked when both rates
+        /// are given, the device doesn't hold them.
+        #[arg(long, requires = "output_rate")]
+        input_rate: Option<u32>,
+        /// The nominal SRC output rate in Hz.
+        #[arg(long, requires = "input_rate")]
+        output_rate: Option<u32>,
+    },
+    #[command(flatten)]
+    Device(DeviceCommand),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Decode { dump } => load(&dump).map(|dump| {
+            print!("{}", decode(&dump));
+            true
+        }),
+        Command::Diff { old, new } => load(&old).and_then(|old| {
+            let differences = diff(&old, &load(&new)?);
+            print!("{}", differences);
+            Ok(differences.is_empty())
+        }),
+        Command::Check {
+            dump,
+            input_rate,
+            output_rate,
+        } => load(&dump).map(|dump| match check(&dump, input_rate.zip(output_rate)) {
+            Some(problem) => {
+                println!("{}", problem);
+                false
+            }
+            None => {
+                println!("no problems found");
+                true
+            }
+        }),
+        Command::Device(command) => device::execute(&cli.backend, &command).map(|out| {
+            print!("{}", out);
+            true
+        }),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn load(path: &Path) -> Result<Vec<u8>, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let bytes = if bytes.starts_with(&dump::MAGIC) {
+        bytes
+    } else {
+        let text = String::from_utf8(bytes)
+            .map_err(|_| format!("{}: neither a binary dump nor hex text", path.display()))?;
+        parse_hex(&text).map_err(|e| format!("{}: {}", path.display(), e))?
+    };
+    RegisterDump::parse(&bytes).map_err(|e| format!("{}: {:?}", path.display(), e))?;
+    Ok(bytes)
+}
+
+/// Parses hex text into dump bytes, adding a header if the text only holds the page 0 registers.
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        for token in line.split(|c: char| c.is_whitespace() || c == ',') {
+            let token = token.trim_start_matches("0x").trim_start_matches("0X");
+            if token.len() % 2 != 0 {
+                return Err(format!("`{}` isn't a whole number of bytes", token));
+            }
+            for index in (0..token.len()).step_by(2) {
+                let byte = u8::from_str_radix(&token[index..index + 2], 16)
+                    .map_err(|_| format!("`{}` isn't hex", token))?;
+                bytes.push(byte);
+            }
+        }
+    }
+    if bytes.starts_with(&dump::MAGIC) {
+        return Ok(bytes);
+    }
+    if bytes.len() != dump::CONTROL_PAGE_LEN {
+        return Err(format!(
+            "expected a dump or {} page 0 bytes, found {} bytes",
+            dump::CONTROL_PAGE_LEN,
+            bytes.len()
+        ));
+    }
+    let mut dump = vec![0u8; dump::HEADER_LEN];
+    dump::write_header(&mut dump, false);
+    dump.extend(bytes);
+    Ok(dump)
+}
+
+fn registers<'a>(dump: &RegisterDump<'a>) -> impl Iterator<Item = (u8, u8)> + 'a {
+    dump.control_page()
+        .iter()
+        .enumerate()
+        .filter(|(address, _)| Registers::from_address(*address as u8).is_some())
+        .map(|(address, value)| (address as u8, *value))
+}
+
+fn decode(bytes: &[u8]) -> String {
+    let dump = RegisterDump::parse(bytes).unwrap();
+    let mut out = String::new();
+    for (address, value) in registers(&dump) {
+        writeln!(out, "{}", decode_any(address, value)).unwrap();
+    }
+    let buffers = [
+        ("DIR buffers", dump.receiver_buffers()),
+        ("DIT buffers", dump.transmitter_buffers()),
+    ];
+    for (name, buffer) in buffers {
+        if let Some(buffer) = buffer {
+            writeln!(out, "{}:", name).unwrap();
+            for (row, chunk) in buffer.chunks(16).enumerate() {
+                write!(out, "  0x{:02X}:", row * 16).unwrap();
+                for byte in chunk {
+                    write!(out, " {:02X}", byte).unwrap();
+                }
+                writeln!(out).unwrap();
+            }
+        }
+    }
+    out
+}
+
+/// The `FIELD=value` pairs of a decoded register.
+fn fields(decoded: &str) -> Vec<(&str, &str)> {
+    decoded
+        .split_once(": ")
+        .map(|(_, fields)| {
+            fields
+                .split(", ")
+                .filter_map(|field| field.split_once('='))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn diff(old: &[u8], new: &[u8]) -> String {
+    let old = RegisterDump::parse(old).unwrap();
+    let new = RegisterDump::parse(new).unwrap();
+    let mut out = String::new();
+    for ((address, old), (_, new)) in registers(&old).zip(registers(&new)) {
+        if old == new {
+            continue;
+        }
+        let old = decode_any(address, old).to_string();
+        let new = decode_any(address, new).to_string();
+        writeln!(out, "- {}\n+ {}", old, new).unwrap();
+        for ((name, old), (_, new)) in fields(&old).into_iter().zip(fields(&new)) {
+            if old != new {
+                writeln!(out, "    {}: {} -> {}", name, old, new).unwrap();
+            }
+        }
+    }
+    out
+}
+
+/// Decodes the registers covered by [`DeviceConfig`] and validates them, returning the first
+/// problem found. Direct down sampling is only checked when the nominal input and output `rates`
+/// are known.
+fn check(bytes: &[u8], rates: Option<(u32, u32)>) -> Option<String> {
+    let dump = RegisterDump::parse(bytes).unwrap();
+    let mut image = [0u8; 21];
+    for (value, register) in image.iter_mut().zip(DeviceConfig::REGISTERS) {
+        *value = dump.register(register);
+    }
+    let mut config = match DeviceConfig::from_image(&image) {
+        Ok(config) => config,
+        Err(DecodeError { register, value }) => {
+            return Some(format!(
+                "{}: uses a reserved code",
+                decode_any(register as u8, value)
+            ))
+        }
+    };
+
+    // The ratio register only holds a measurement, which says nothing about the rates the setup
+    // was made for.
+    match rates {
+        Some(rates) => config.src.nominal_rates = Some(rates),
+        None => config.src.direct_down_sampling = false,
+    }
+
+    config
+        .validate()
+        .err()
+        .map(|error| describe(error, &config))
+}
+
+fn describe(error: ConfigError, config: &DeviceConfig) -> String {
+    let port = |port: Port| match port {
+        Port::A => "port A",
+        Port::B => "port B",
+    };
+    match error {
+        ConfigError::RoutingLoop(p) => {
+            format!(
+                "{} outputs the SRC, which takes its input from {0}",
+                port(p)
+            )
+        }
+        ConfigError::DirectDownSamplingWithoutUpsampling => match config.src.nominal_rates {
+            Some((input, output)) => format!(
+                "direct down sampling (DDN) is used while the SRC isn't upsampling, from {} Hz to \
+                 {} Hz",
+                input, output
+            ),
+            None => "direct down sampling (DDN) is used but the SRC rates are unknown".into(),
+        },
+        ConfigError::MissingPortClock(p) => format!(
+            "{} is a master clocked from RXCKO, but the recovered clock output is off",
+            port(p)
+        ),
+        ConfigError::MissingSrcClock => {
+            "the SRC is clocked from RXCKO, but the recovered clock output is off".into()
+        }
+        ConfigError::MissingTransmitterClock => {
+            "the DIT is clocked from RXCKI, which the board doesn't have".into()
+        }
+        ConfigError::OutputSourcePoweredDown(p) => format!(
+            "{} outputs {:?}, which is powered down",
+            port(p),
+            config.port(p).output_data_source
+        ),
+        ConfigError::SrcSourcePoweredDown => {
+            format!("the SRC input, {:?}, is powered down", config.src.source)
+        }
+        ConfigError::ReservedSetting(register) => format!("{:?} uses a reserved code", register),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(registers: &[(Registers, u8)]) -> Vec<u8> {
+        let mut page = [0u8; dump::CONTROL_PAGE_LEN];
+        for (register, value) in registers {
+            page[*register as usize] = *value;
+        }
+        let text = page
+            .iter()
+            .map(|b| format!("{:02x} ", b))
+            .collect::<String>();
+        parse_hex(&format!("# page 0\n{}", text)).unwrap()
+    }
+
+    #[test]
+    fn hex_text() {
+        let bytes = parse_hex("0x53,0x33 3932 # magic\n01 00 00 00").unwrap();
+        assert_eq!(bytes, b"S392\x01\x00\x00\x00");
+        assert!(parse_hex("123").is_err());
+        assert!(parse_hex("01 02").is_err());
+    }
+
+    #[test]
+    fn diff_lists_changed_fields() {
+        let old = page(&[(Registers::PortAControl, 0x19)]);
+        let new = page(&[(Registers::PortAControl, 0x39)]);
+        assert_eq!(
+            diff(&old, &new),
+            "- PortAControl(0x03)=0x19: AFMT=I2S, AM_S=master, AOUT=OtherPort, AMUTE=0\n\
+             + PortAControl(0x03)=0x39: AFMT=I2S, AM_S=master, AOUT=SRC, AMUTE=0\n    \
+             AOUT: OtherPort -> SRC\n"
+        );
+        assert_eq!(diff(&old, &old), "");
+    }
+
+    #[test]
+    fn check_finds_inconsistent_setups() {
+        // Everything but the SRC powered up, port A outputs the SRC fed from the DIR.
+        let dump = page(&[
+            (Registers::Reset, 0b0011_1110),
+            (Registers::PortAControl, 0x30),
+            (Registers::SrcControl, 0x02),
+        ]);
+        assert_eq!(
+            check(&dump, None).unwrap(),
+            "port A outputs SRC, which is powered down"
+        );
+
+        // DDN, only checked when the rates are given.
+        let dump = page(&[
+            (Registers::Reset, 0b0011_1111),
+            (Registers::SrcControl2, 0b0000_0100),
+        ]);
+        assert!(check(&dump, Some((96_000, 48_000)))
+            .unwrap()
+            .contains("from 96000 Hz to 48000 Hz"));
+        assert_eq!(check(&dump, Some((44_100, 48_000))), None);
+        assert_eq!(check(&dump, None), None);
+
+        let dump = page(&[(Registers::Reset, 0b0011_1111)]);
+        assert_eq!(check(&dump, None), None);
+    }
+}